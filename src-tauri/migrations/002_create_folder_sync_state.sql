@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS folder_sync_state (
+    script_id TEXT PRIMARY KEY,
+    file_name TEXT NOT NULL,
+    fingerprint TEXT NOT NULL,
+    synced_updated_at TEXT NOT NULL
+);
@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS tombstones (
+    script_id TEXT PRIMARY KEY,
+    deleted_at TEXT NOT NULL
+);
+
+CREATE TRIGGER IF NOT EXISTS scripts_record_tombstone AFTER DELETE ON scripts
+BEGIN
+    INSERT OR REPLACE INTO tombstones (script_id, deleted_at)
+    VALUES (old.id, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS scripts_clear_tombstone AFTER INSERT ON scripts
+BEGIN
+    DELETE FROM tombstones WHERE script_id = new.id;
+END;
+
+CREATE TABLE IF NOT EXISTS webdav_sync_state (
+    script_id TEXT PRIMARY KEY,
+    synced_updated_at TEXT NOT NULL,
+    remote_version TEXT NOT NULL
+);
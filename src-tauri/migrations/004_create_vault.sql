@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS vault (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    salt TEXT NOT NULL,
+    wrapped_key TEXT NOT NULL,
+    auto_lock_minutes INTEGER
+);
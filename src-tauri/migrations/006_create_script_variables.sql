@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS script_variables (
+    script_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (script_id, name)
+);
+
+CREATE TRIGGER IF NOT EXISTS scripts_delete_variables AFTER DELETE ON scripts
+BEGIN
+    DELETE FROM script_variables WHERE script_id = old.id;
+END;
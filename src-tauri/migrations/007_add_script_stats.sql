@@ -0,0 +1,12 @@
+SAVEPOINT add_script_stats;
+
+ALTER TABLE scripts ADD COLUMN word_count INTEGER;
+ALTER TABLE scripts ADD COLUMN line_count INTEGER;
+
+CREATE TRIGGER IF NOT EXISTS scripts_clear_stats AFTER UPDATE OF content ON scripts
+WHEN old.content IS NOT new.content
+BEGIN
+    UPDATE scripts SET word_count = NULL, line_count = NULL WHERE id = new.id;
+END;
+
+RELEASE add_script_stats;
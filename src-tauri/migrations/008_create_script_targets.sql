@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS script_targets (
+    script_id TEXT PRIMARY KEY,
+    target_seconds REAL NOT NULL
+);
+
+CREATE TRIGGER IF NOT EXISTS scripts_delete_target AFTER DELETE ON scripts
+BEGIN
+    DELETE FROM script_targets WHERE script_id = old.id;
+END;
@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS sessions (
+    id TEXT PRIMARY KEY,
+    script_id TEXT NOT NULL,
+    mode TEXT NOT NULL,
+    started_at TEXT NOT NULL,
+    ended_at TEXT,
+    duration_seconds REAL NOT NULL DEFAULT 0,
+    pauses INTEGER NOT NULL DEFAULT 0,
+    speed_changes INTEGER NOT NULL DEFAULT 0,
+    completion REAL NOT NULL DEFAULT 0,
+    word_count INTEGER NOT NULL DEFAULT 0,
+    actual_wpm REAL
+);
+
+CREATE INDEX IF NOT EXISTS idx_sessions_script ON sessions (script_id, started_at);
+
+CREATE TRIGGER IF NOT EXISTS scripts_delete_sessions AFTER DELETE ON scripts
+BEGIN
+    DELETE FROM sessions WHERE script_id = old.id;
+END;
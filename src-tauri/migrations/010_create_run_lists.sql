@@ -0,0 +1,27 @@
+CREATE TABLE IF NOT EXISTS run_lists (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    end_action TEXT NOT NULL DEFAULT 'advance',
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS run_list_items (
+    run_list_id TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    script_id TEXT NOT NULL,
+    mode TEXT,
+    speed REAL,
+    gap_seconds REAL NOT NULL DEFAULT 0,
+    PRIMARY KEY (run_list_id, position)
+);
+
+CREATE TRIGGER IF NOT EXISTS run_lists_delete_items AFTER DELETE ON run_lists
+BEGIN
+    DELETE FROM run_list_items WHERE run_list_id = old.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS scripts_delete_run_list_items AFTER DELETE ON scripts
+BEGIN
+    DELETE FROM run_list_items WHERE script_id = old.id;
+END;
@@ -0,0 +1,210 @@
+//! Reading-time estimates for a parsed script.
+//!
+//! The prompter moves at a steady rate of three words a minute per point of
+//! scroll speed, so a script takes its word count divided by that rate,
+//! after the countdown. Counts follow the engine: placeholders are one word,
+//! cues and section titles none, and each paragraph is a line.
+//!
+//! Pause cues don't stop the scroll. A section with `[PAUSE]`s has to be read
+//! faster to keep up, which is what its `target_wpm` says.
+//!
+//! A script with a target duration is prompted at the speed that reaches its
+//! last word that long after scrolling starts.
+
+use crate::markup::{Document, Inline};
+use crate::text;
+use serde::Serialize;
+
+/// Seconds a presenter is expected to stay silent at a cue. `[PAUSE 3]`
+/// gives its own length.
+fn cue_seconds(name: &str, argument: Option<&str>) -> f64 {
+    let default = match name {
+        "PAUSE" => 2.0,
+        "BEAT" | "BREATH" | "BREATHE" => 1.0,
+        _ => return 0.0,
+    };
+    argument
+        .and_then(|a| a.trim_end_matches('s').trim().parse::<f64>().ok())
+        .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+        .unwrap_or(default)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Analysis {
+    pub word_count: u32,
+    pub line_count: u32,
+    /// Words per minute at the scroll speed analyzed for
+    pub wpm: f64,
+    pub estimated_seconds: f64,
+    pub sections: Vec<SectionAnalysis>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SectionAnalysis {
+    pub title: Option<String>,
+    pub line: usize,
+    pub word_count: u32,
+    pub estimated_seconds: f64,
+    pub pause_seconds: f64,
+    /// Reading rate that keeps up with the scroll once pauses are taken
+    pub target_wpm: f64,
+}
+
+/// The range the prompter accepts, as in the settings schema.
+pub const MIN_SPEED: f64 = 10.0;
+pub const MAX_SPEED: f64 = 200.0;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TargetSpeed {
+    pub target_seconds: f64,
+    pub speed: f64,
+    pub wpm: f64,
+    /// Time to the last word at `speed`. Differs from the target only when
+    /// the speed it needs is out of range (`clamped`).
+    pub estimated_seconds: f64,
+    /// How fast the text moves at the mode's font size
+    pub pixels_per_second: f64,
+    pub clamped: bool,
+}
+
+/// Words per minute at an engine scroll speed.
+pub fn wpm(speed: f64) -> f64 {
+    speed * 3.0
+}
+
+pub fn estimated_seconds(word_count: u32, speed: f64) -> f64 {
+    word_count as f64 / wpm(speed) * 60.0
+}
+
+/// Word and line counts, as stored with each script.
+pub fn counts(document: &Document) -> (u32, u32) {
+    let mut words = 0;
+    let mut lines = 0;
+    for paragraph in document.sections.iter().flat_map(|s| &s.paragraphs) {
+        let count = paragraph_words(&paragraph.inlines);
+        words += count;
+        if count > 0 {
+            lines += 1;
+        }
+    }
+    (words, lines)
+}
+
+pub fn analyze(document: &Document, speed: f64) -> Analysis {
+    let (word_count, line_count) = counts(document);
+    let sections = document
+        .sections
+        .iter()
+        .map(|section| {
+            let inlines = section.paragraphs.iter().flat_map(|p| &p.inlines);
+            let word_count: u32 = section.paragraphs.iter().map(|p| paragraph_words(&p.inlines)).sum();
+            let pause_seconds: f64 = inlines
+                .filter_map(|inline| match inline {
+                    Inline::Cue { name, argument, .. } => Some(cue_seconds(name, argument.as_deref())),
+                    _ => None,
+                })
+                .sum();
+            let seconds = estimated_seconds(word_count, speed);
+            let reading = seconds - pause_seconds;
+            SectionAnalysis {
+                title: section.title.clone(),
+                line: section.line,
+                word_count,
+                estimated_seconds: seconds,
+                pause_seconds,
+                target_wpm: if reading > 0.0 {
+                    word_count as f64 / reading * 60.0
+                } else {
+                    wpm(speed)
+                },
+            }
+        })
+        .collect();
+
+    Analysis {
+        word_count,
+        line_count,
+        wpm: wpm(speed),
+        estimated_seconds: estimated_seconds(word_count, speed),
+        sections,
+    }
+}
+
+/// The speed that takes `target_seconds` from the start of scrolling (the
+/// countdown isn't counted) to the last word. The engine starts on the
+/// first word, so one word fewer than the count is scrolled past.
+pub fn target_speed(document: &Document, target_seconds: f64, font_size: f64) -> TargetSpeed {
+    let (word_count, line_count) = counts(document);
+    let scrolled = word_count.saturating_sub(1).max(1) as f64;
+    let needed = scrolled / target_seconds * 60.0 / wpm(1.0);
+    let speed = needed.clamp(MIN_SPEED, MAX_SPEED);
+
+    // As the engine derives it: a line is 1.2 em plus 16px of spacing
+    let words_per_second = wpm(speed) / 60.0;
+    let line_height = font_size * 1.2 + 16.0;
+    let words_per_line = (word_count as f64 / line_count.max(1) as f64).max(1.0);
+
+    TargetSpeed {
+        target_seconds,
+        speed,
+        wpm: wpm(speed),
+        estimated_seconds: scrolled / words_per_second,
+        pixels_per_second: words_per_second / words_per_line * line_height,
+        clamped: speed != needed,
+    }
+}
+
+fn paragraph_words(inlines: &[Inline]) -> u32 {
+    inlines
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text { text, .. } => text::word_count(text) as u32,
+            Inline::Placeholder { .. } => 1,
+            Inline::Cue { .. } => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markup;
+
+    #[test]
+    fn duration_follows_scroll_speed() {
+        let doc = markup::parse("# Intro\n\nHello [Name] and welcome.\n\n// not read\nSecond line here");
+        let analysis = analyze(&doc, 50.0);
+        assert_eq!((analysis.word_count, analysis.line_count), (7, 2));
+        assert_eq!(analysis.wpm, 150.0);
+        assert!((analysis.estimated_seconds - 2.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cjk_sections_and_pauses() {
+        let doc = markup::parse("# 開始\n\nこんにちは。[PAUSE 1]\n\n# Next\n\none two three [BEAT]");
+        let analysis = analyze(&doc, 10.0);
+        let opening = &analysis.sections[0];
+        assert_eq!(opening.title.as_deref(), Some("開始"));
+        assert_eq!(opening.word_count, 5);
+        assert_eq!(opening.pause_seconds, 1.0);
+        assert!((opening.estimated_seconds - 10.0).abs() < 1e-9);
+        assert!((opening.target_wpm - 5.0 / 9.0 * 60.0).abs() < 1e-9);
+        assert_eq!(analysis.sections[1].pause_seconds, 1.0);
+    }
+
+    #[test]
+    fn target_duration_sets_the_speed() {
+        let doc = markup::parse(&format!("{}\n\n{}", ["word"; 301].join(" "), ["word"; 300].join(" ")));
+        let target = target_speed(&doc, 150.0, 20.0);
+        // 601 words: 600 scrolled past in 2:30 is 240 wpm
+        assert!((target.speed - 80.0).abs() < 1e-9);
+        assert!((target.estimated_seconds - 150.0).abs() < 1e-9);
+        assert!(!target.clamped);
+        assert!((target.pixels_per_second - 4.0 / 300.5 * 40.0).abs() < 1e-9);
+
+        let rushed = target_speed(&doc, 10.0, 20.0);
+        assert_eq!(rushed.speed, MAX_SPEED);
+        assert!(rushed.clamped);
+        assert!(rushed.estimated_seconds > 10.0);
+    }
+}
@@ -0,0 +1,48 @@
+use crate::cues::{CueConfig, CueEvent, CueOutput};
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn get_cue_outputs() -> AppResult<Vec<CueOutput>> {
+    Ok(CueConfig::load().map_err(AppError::cue_output)?.outputs)
+}
+
+/// Add an output, or replace the one with the same id.
+#[tauri::command]
+pub fn save_cue_output(mut output: CueOutput) -> AppResult<Vec<CueOutput>> {
+    output.validate().map_err(AppError::cue_output)?;
+    let mut config = CueConfig::load().map_err(AppError::cue_output)?;
+    match config.outputs.iter_mut().find(|o| !output.id.is_empty() && o.id == output.id) {
+        Some(existing) => *existing = output,
+        None => {
+            output.id = uuid::Uuid::new_v4().to_string();
+            config.outputs.push(output);
+        }
+    }
+    config.save().map_err(AppError::cue_output)?;
+    Ok(config.outputs)
+}
+
+#[tauri::command]
+pub fn delete_cue_output(id: String) -> AppResult<Vec<CueOutput>> {
+    let mut config = CueConfig::load().map_err(AppError::cue_output)?;
+    config.outputs.retain(|o| o.id != id);
+    config.save().map_err(AppError::cue_output)?;
+    Ok(config.outputs)
+}
+
+/// Fire an output once with a sample cue and wait for the result, whether
+/// or not it's saved or enabled.
+#[tauri::command]
+pub fn test_cue_output(output: CueOutput) -> AppResult<()> {
+    output.validate().map_err(AppError::cue_output)?;
+    output.output.fire(&CueEvent::sample(&output)).map_err(AppError::cue_output)
+}
+
+/// Called by the prompter as the scroll reaches a cue, with the cue's
+/// position among the script's cues.
+#[tauri::command]
+pub fn report_cue(app: AppHandle, state: State<AppState>, index: usize) -> AppResult<CueEvent> {
+    state.cues.dispatch(&app, index)
+}
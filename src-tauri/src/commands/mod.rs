@@ -1,4 +1,11 @@
+pub mod cues;
+pub mod profiles;
+pub mod recovery;
+pub mod run_lists;
 pub mod scripts;
+pub mod sessions;
 pub mod settings;
+pub mod sync;
 pub mod system;
+pub mod vault;
 pub mod window;
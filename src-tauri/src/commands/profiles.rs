@@ -0,0 +1,93 @@
+use crate::error::AppResult;
+use crate::profiles::{ProfileSummary, Profiles};
+use crate::settings;
+use crate::state::AppState;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn list_profiles(state: State<AppState>) -> AppResult<Vec<ProfileSummary>> {
+    Ok(Profiles::load(&*state.db.conn.lock()?)?.summaries())
+}
+
+/// Save the current settings as a new profile and make it the active one.
+#[tauri::command]
+pub fn create_profile(
+    app: AppHandle,
+    state: State<AppState>,
+    name: String,
+) -> AppResult<Vec<ProfileSummary>> {
+    let _guard = settings::write_lock();
+    edit(&app, &state, |tx, profiles| {
+        let id = profiles.create(&name, settings::load(tx)?)?;
+        profiles.active = Some(id);
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn duplicate_profile(
+    app: AppHandle,
+    state: State<AppState>,
+    id: String,
+) -> AppResult<Vec<ProfileSummary>> {
+    edit(&app, &state, |_, profiles| profiles.duplicate(&id).map(|_| ()))
+}
+
+#[tauri::command]
+pub fn rename_profile(
+    app: AppHandle,
+    state: State<AppState>,
+    id: String,
+    name: String,
+) -> AppResult<Vec<ProfileSummary>> {
+    edit(&app, &state, |_, profiles| profiles.rename(&id, &name))
+}
+
+/// Deleting the active profile leaves the current settings in place.
+#[tauri::command]
+pub fn delete_profile(
+    app: AppHandle,
+    state: State<AppState>,
+    id: String,
+) -> AppResult<Vec<ProfileSummary>> {
+    edit(&app, &state, |_, profiles| profiles.delete(&id))
+}
+
+/// Switch to a profile's settings.
+#[tauri::command]
+pub fn activate_profile(
+    app: AppHandle,
+    state: State<AppState>,
+    id: String,
+) -> AppResult<Vec<ProfileSummary>> {
+    let _guard = settings::write_lock();
+    // Marked active in the same transaction that applies its settings, so a
+    // profile that can't be applied isn't left showing as active
+    let (previous, settings) =
+        crate::commands::settings::store_live(&app, &state.db, "profile", |tx, _| {
+            let mut profiles = Profiles::load(tx)?;
+            let settings = profiles.get(&id)?.settings.clone();
+            profiles.active = Some(id);
+            profiles.save(tx)?;
+            Ok(settings)
+        })?;
+    crate::commands::settings::broadcast(&app, &previous, settings);
+    crate::tray::refresh(&app);
+    list_profiles(state)
+}
+
+/// Load, change and save the profiles in one transaction, then refresh the tray.
+fn edit(
+    app: &AppHandle,
+    state: &AppState,
+    change: impl FnOnce(&rusqlite::Transaction, &mut Profiles) -> AppResult<()>,
+) -> AppResult<Vec<ProfileSummary>> {
+    let profiles = state.db.transaction(|tx| {
+        let mut profiles = Profiles::load(tx)?;
+        change(tx, &mut profiles)?;
+        profiles.save(tx)?;
+        Ok(profiles)
+    })?;
+    crate::tray::refresh(app);
+    Ok(profiles.summaries())
+}
@@ -0,0 +1,71 @@
+use crate::db::Database;
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::recovery::{self, RecoveryState, StartupFailure};
+use tauri::{AppHandle, Manager};
+
+#[tauri::command]
+pub fn get_startup_error(app: AppHandle) -> AppResult<Option<StartupFailure>> {
+    let Some(state) = app.try_state::<RecoveryState>() else {
+        return Ok(None);
+    };
+    let failure = state.failure.lock()?;
+    Ok(failure.clone())
+}
+
+/// Try opening the database again, e.g. after closing another program holding the lock.
+#[tauri::command]
+pub fn recovery_retry(app: AppHandle) -> AppResult<()> {
+    attempt(&app, Database::new)
+}
+
+/// Move the broken database aside and replace it with the last good backup.
+#[tauri::command]
+pub fn recovery_restore_backup(app: AppHandle) -> AppResult<()> {
+    let backup = Database::backup_path();
+    if !backup.exists() {
+        return Err(AppError::new(ErrorCode::NotFound, "No backup is available"));
+    }
+
+    let db_path = Database::db_path();
+    if db_path.exists() {
+        recovery::move_aside(&db_path)?;
+    }
+    std::fs::copy(&backup, &db_path)?;
+
+    attempt(&app, Database::new)
+}
+
+/// Move the broken database aside and start with an empty library.
+#[tauri::command]
+pub fn recovery_start_fresh(app: AppHandle) -> AppResult<()> {
+    let db_path = Database::db_path();
+    if db_path.exists() {
+        recovery::move_aside(&db_path)?;
+    }
+
+    attempt(&app, Database::new)
+}
+
+/// Open the existing database without writing to it.
+#[tauri::command]
+pub fn recovery_open_read_only(app: AppHandle) -> AppResult<()> {
+    attempt(&app, Database::open_read_only)
+}
+
+fn attempt(app: &AppHandle, open: fn() -> rusqlite::Result<Database>) -> AppResult<()> {
+    match open() {
+        Ok(db) => {
+            recovery::finish(app, db);
+            Ok(())
+        }
+        Err(e) => {
+            let failure = recovery::record_failure(&e);
+            if let Some(state) = app.try_state::<RecoveryState>() {
+                if let Ok(mut current) = state.failure.lock() {
+                    *current = Some(failure);
+                }
+            }
+            Err(e.into())
+        }
+    }
+}
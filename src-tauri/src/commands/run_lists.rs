@@ -0,0 +1,171 @@
+use crate::commands::window::PromptOverrides;
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::models::{EndAction, RunList, RunListStatus};
+use crate::run_lists::{self, ActiveRun};
+use crate::state::AppState;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+#[tauri::command]
+pub fn list_run_lists(state: State<AppState>) -> AppResult<Vec<RunList>> {
+    let conn = state.db.conn.lock()?;
+    run_lists::list(&conn)
+}
+
+/// Create a run list, or replace the one with the same id.
+#[tauri::command]
+pub fn save_run_list(state: State<AppState>, run_list: RunList) -> AppResult<RunList> {
+    let mut run_list = run_list;
+    state.db.transaction(|tx| run_lists::save(tx, &mut run_list))?;
+    Ok(run_list)
+}
+
+#[tauri::command]
+pub fn delete_run_list(state: State<AppState>, id: String) -> AppResult<()> {
+    state.db.ensure_writable()?;
+    let conn = state.db.conn.lock()?;
+    run_lists::delete(&conn, &id)
+}
+
+#[tauri::command]
+pub fn get_run_list_status(state: State<AppState>) -> AppResult<Option<RunListStatus>> {
+    Ok(state.run.lock()?.as_ref().map(ActiveRun::status))
+}
+
+/// Start prompting a run list at its first item, or at `index`. Placeholders
+/// left unfilled in any of its scripts refuse the start unless
+/// `allow_unresolved`, so the show doesn't stop halfway through.
+#[tauri::command]
+pub fn start_run_list(
+    app: AppHandle,
+    state: State<AppState>,
+    id: String,
+    index: Option<usize>,
+    allow_unresolved: Option<bool>,
+) -> AppResult<RunListStatus> {
+    let list = {
+        let conn = state.db.conn.lock()?;
+        run_lists::get(&conn, &id)?
+    };
+    if list.items.is_empty() {
+        return Err(AppError::new(ErrorCode::InvalidData, "Add scripts to the run list first"));
+    }
+    if !allow_unresolved.unwrap_or(false) {
+        let unresolved = unresolved_placeholders(&state, &list)?;
+        if !unresolved.is_empty() {
+            return Err(AppError::unresolved_placeholders(unresolved));
+        }
+    }
+
+    let index = index.unwrap_or(0).min(list.items.len() - 1);
+    *state.run.lock()? = Some(ActiveRun::new(list, index));
+    // A key taken by another program leaves the prompter's own buttons
+    crate::shortcuts::register_run_list(&app).ok();
+    move_to(&app, &state, |_| Some(index))?
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, "The run list stopped before it started"))
+}
+
+#[tauri::command]
+pub fn run_list_next(app: AppHandle, state: State<AppState>) -> AppResult<Option<RunListStatus>> {
+    step(&app, &state, 1)
+}
+
+#[tauri::command]
+pub fn run_list_previous(app: AppHandle, state: State<AppState>) -> AppResult<Option<RunListStatus>> {
+    step(&app, &state, -1)
+}
+
+/// Go `delta` items along the running list. Does nothing with no list
+/// running or at either end of one that doesn't loop.
+pub fn step(app: &AppHandle, state: &AppState, delta: isize) -> AppResult<Option<RunListStatus>> {
+    move_to(app, state, |run| run.step(delta))
+}
+
+/// The current script's prompter reached its end: move on after the item's
+/// gap if the list's `end_action` says to. A move made meanwhile, with Next
+/// or Previous, cancels this one.
+pub fn script_finished(app: &AppHandle, state: &AppState) -> AppResult<()> {
+    let pending = state
+        .run
+        .lock()?
+        .as_ref()
+        .and_then(|run| Some((run.after_finish()?, run.item().gap_seconds, run.generation())));
+    let Some((next, gap_seconds, generation)) = pending else {
+        return Ok(());
+    };
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_secs_f64(gap_seconds));
+        if let Some(state) = app.try_state::<AppState>() {
+            move_to(&app, &state, |run| (run.generation() == generation).then_some(next)).ok();
+        }
+    });
+    Ok(())
+}
+
+/// Stop the running list, if any, when its prompter closes.
+pub fn end_run(app: &AppHandle, state: &AppState) -> AppResult<()> {
+    if state.run.lock()?.take().is_some() {
+        crate::shortcuts::unregister_run_list(app);
+        app.emit("run-list-changed", None::<RunListStatus>).ok();
+    }
+    Ok(())
+}
+
+/// Make the item `choose` picks current and open its prompter. Every item
+/// stops at its end, so the prompter reports finishing instead of looping
+/// or closing.
+fn move_to(
+    app: &AppHandle,
+    state: &AppState,
+    choose: impl FnOnce(&ActiveRun) -> Option<usize>,
+) -> AppResult<Option<RunListStatus>> {
+    let (item, status) = {
+        let mut run = state.run.lock()?;
+        let Some(run) = run.as_mut() else {
+            return Ok(None);
+        };
+        let Some(index) = choose(run) else {
+            return Ok(None);
+        };
+        run.go_to(index);
+        (run.item().clone(), run.status())
+    };
+
+    let mode = match item.mode {
+        Some(mode) => mode,
+        None => crate::settings::load(&*state.db.conn.lock()?)?.prompter_mode,
+    };
+    let overrides = PromptOverrides {
+        speed: item.speed,
+        end_action: Some(EndAction::Stop),
+    };
+    // Placeholders were checked when the list started
+    crate::commands::window::open(app, state, &item.script_id, mode, true, &overrides)?;
+    app.emit("run-list-changed", Some(&status)).ok();
+    Ok(Some(status))
+}
+
+/// Placeholders still without a value across the list's scripts.
+fn unresolved_placeholders(state: &AppState, list: &RunList) -> AppResult<Vec<String>> {
+    let conn = state.db.conn.lock()?;
+    let mut names: Vec<String> = Vec::new();
+    for item in &list.items {
+        let content: String = conn
+            .query_row(
+                "SELECT content FROM scripts WHERE id = ?1",
+                rusqlite::params![item.script_id],
+                |row| row.get(0),
+            )
+            .map_err(AppError::script_lookup(&item.script_id))?;
+        let content = state.db.vault.open(content)?;
+        let document = crate::variables::filled_document(&conn, &state.db.vault, &item.script_id, &content)?;
+        for name in document.placeholders() {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    Ok(names)
+}
@@ -1,13 +1,22 @@
-use crate::models::Script;
+use crate::analysis::{self, Analysis, TargetSpeed};
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::markup::{self, Document};
+use crate::models::{PrompterMode, Script, ScriptVariable};
 use crate::state::AppState;
-use tauri::State;
+use crate::variables;
+use rusqlite::{Connection, DatabaseName, OptionalExtension};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, Manager, State};
 
 #[tauri::command]
-pub fn get_all_scripts(state: State<AppState>) -> Result<Vec<Script>, String> {
-    let conn = state.db.conn.lock().map_err(|e| e.to_string())?;
+pub fn get_all_scripts(app: AppHandle, state: State<AppState>) -> AppResult<Vec<Script>> {
+    let conn = state.db.conn.lock()?;
     let mut stmt = conn
-        .prepare("SELECT id, title, content, created_at, updated_at FROM scripts ORDER BY updated_at DESC")
-        .map_err(|e| e.to_string())?;
+        .prepare(&format!(
+            "SELECT id, title, content, created_at, updated_at, {} FROM scripts ORDER BY updated_at DESC",
+            word_count_column(&conn)?
+        ))?;
 
     let scripts = stmt
         .query_map([], |row| {
@@ -17,26 +26,41 @@ pub fn get_all_scripts(state: State<AppState>) -> Result<Vec<Script>, String> {
                 content: row.get(2)?,
                 created_at: row.get(3)?,
                 updated_at: row.get(4)?,
+                word_count: row.get(5)?,
+                estimated_seconds: None,
             })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
 
+    state.db.vault.touch();
+    let (scripts, uncounted) = with_stats(&state, &conn, scripts)?;
+    if uncounted {
+        spawn_count_pending(&app);
+    }
     Ok(scripts)
 }
 
 #[tauri::command]
-pub fn create_script(state: State<AppState>, title: String, content: String) -> Result<Script, String> {
-    let conn = state.db.conn.lock().map_err(|e| e.to_string())?;
+pub fn create_script(
+    app: AppHandle,
+    state: State<AppState>,
+    title: String,
+    content: String,
+) -> AppResult<Script> {
+    state.db.ensure_writable()?;
+    let conn = state.db.conn.lock()?;
     let id = uuid::Uuid::new_v4().to_string();
     let now = chrono::Utc::now().to_rfc3339();
 
     conn.execute(
         "INSERT INTO scripts (id, title, content, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)",
-        rusqlite::params![id, title, content, now, now],
-    )
-    .map_err(|e| e.to_string())?;
+        rusqlite::params![id, title, state.db.vault.seal(&content)?, now, now],
+    )?;
+    let (words, _) = store_counts(&state, &conn, &id, &markup::parse(&content))?;
+    state.db.vault.touch();
+    state.folder_sync.request_sync();
+    drop(conn);
+    crate::tray::refresh(&app);
 
     Ok(Script {
         id,
@@ -44,44 +68,63 @@ pub fn create_script(state: State<AppState>, title: String, content: String) ->
         content,
         created_at: now.clone(),
         updated_at: now,
+        word_count: Some(words),
+        estimated_seconds: None,
     })
 }
 
 #[tauri::command]
 pub fn update_script(
+    app: AppHandle,
     state: State<AppState>,
     id: String,
     title: String,
     content: String,
-) -> Result<(), String> {
-    let conn = state.db.conn.lock().map_err(|e| e.to_string())?;
+) -> AppResult<()> {
+    state.db.ensure_writable()?;
+    let conn = state.db.conn.lock()?;
     let now = chrono::Utc::now().to_rfc3339();
+    let document = variables::filled_document(&conn, &state.db.vault, &id, &content)?;
 
-    conn.execute(
+    let updated = conn.execute(
         "UPDATE scripts SET title = ?1, content = ?2, updated_at = ?3 WHERE id = ?4",
-        rusqlite::params![title, content, now, id],
-    )
-    .map_err(|e| e.to_string())?;
+        rusqlite::params![title, state.db.vault.seal(&content)?, now, id],
+    )?;
+    if updated == 0 {
+        return Err(AppError::script_not_found(&id));
+    }
+    // After the update, whose trigger clears the old counts
+    store_counts(&state, &conn, &id, &document)?;
+    state.db.vault.touch();
+    state.folder_sync.request_sync();
+    drop(conn);
+    crate::tray::refresh(&app);
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn delete_script(state: State<AppState>, id: String) -> Result<(), String> {
-    let conn = state.db.conn.lock().map_err(|e| e.to_string())?;
+pub fn delete_script(app: AppHandle, state: State<AppState>, id: String) -> AppResult<()> {
+    state.db.ensure_writable()?;
+    let conn = state.db.conn.lock()?;
 
-    conn.execute("DELETE FROM scripts WHERE id = ?1", rusqlite::params![id])
-        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM scripts WHERE id = ?1", rusqlite::params![id])?;
+    state.db.vault.touch();
+    state.folder_sync.request_sync();
+    drop(conn);
+    crate::tray::refresh(&app);
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn get_recent_scripts(state: State<AppState>, limit: u32) -> Result<Vec<Script>, String> {
-    let conn = state.db.conn.lock().map_err(|e| e.to_string())?;
+pub fn get_recent_scripts(app: AppHandle, state: State<AppState>, limit: u32) -> AppResult<Vec<Script>> {
+    let conn = state.db.conn.lock()?;
     let mut stmt = conn
-        .prepare("SELECT id, title, content, created_at, updated_at FROM scripts ORDER BY updated_at DESC LIMIT ?1")
-        .map_err(|e| e.to_string())?;
+        .prepare(&format!(
+            "SELECT id, title, content, created_at, updated_at, {} FROM scripts ORDER BY updated_at DESC LIMIT ?1",
+            word_count_column(&conn)?
+        ))?;
 
     let scripts = stmt
         .query_map(rusqlite::params![limit], |row| {
@@ -91,12 +134,286 @@ pub fn get_recent_scripts(state: State<AppState>, limit: u32) -> Result<Vec<Scri
                 content: row.get(2)?,
                 created_at: row.get(3)?,
                 updated_at: row.get(4)?,
+                word_count: row.get(5)?,
+                estimated_seconds: None,
             })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
 
+    state.db.vault.touch();
+    let (scripts, uncounted) = with_stats(&state, &conn, scripts)?;
+    if uncounted {
+        spawn_count_pending(&app);
+    }
     Ok(scripts)
 }
 
+/// The document a prompter would show for `content`, with any markup problems.
+#[tauri::command]
+pub fn parse_script(content: String) -> Document {
+    markup::parse(&content)
+}
+
+/// Every placeholder in the script, in order, with its stored value.
+#[tauri::command]
+pub fn get_script_variables(state: State<AppState>, script_id: String) -> AppResult<Vec<ScriptVariable>> {
+    let conn = state.db.conn.lock()?;
+    let content: String = conn
+        .query_row(
+            "SELECT content FROM scripts WHERE id = ?1",
+            rusqlite::params![script_id],
+            |row| row.get(0),
+        )
+        .map_err(AppError::script_lookup(&script_id))?;
+    let mut values = variables::load(&conn, &state.db.vault, &script_id)?;
+    let content = state.db.vault.open(content)?;
+    state.db.vault.touch();
+
+    Ok(markup::parse(&content)
+        .placeholders()
+        .into_iter()
+        .map(|name| ScriptVariable {
+            value: values.remove(&name),
+            name,
+        })
+        .collect())
+}
+
+/// Store values for a script's placeholders. An empty value clears one.
+#[tauri::command]
+pub fn set_script_variables(
+    state: State<AppState>,
+    script_id: String,
+    values: HashMap<String, String>,
+) -> AppResult<()> {
+    state.db.transaction(|tx| {
+        let content: String = tx
+            .query_row(
+                "SELECT content FROM scripts WHERE id = ?1",
+                rusqlite::params![script_id],
+                |row| row.get(0),
+            )
+            .map_err(AppError::script_lookup(&script_id))?;
+        variables::store(tx, &state.db.vault, &script_id, &values)?;
+        // Filled-in values change the word count
+        let content = state.db.vault.open(content)?;
+        let document = variables::filled_document(tx, &state.db.vault, &script_id, &content)?;
+        store_counts(&state, tx, &script_id, &document)?;
+        Ok(())
+    })?;
+    state.db.vault.touch();
+    Ok(())
+}
+
+/// `word_count`, or NULL in a library opened read-only from before it was stored.
+fn word_count_column(conn: &Connection) -> AppResult<&'static str> {
+    Ok(if crate::db::has_column(conn, "scripts", "word_count")? {
+        "word_count"
+    } else {
+        "NULL"
+    })
+}
+
+/// Open each script and add its stored word count and duration. Never
+/// writes; also returns whether any script has no counts yet, which
+/// `spawn_count_pending` fills in.
+fn with_stats(state: &AppState, conn: &Connection, scripts: Vec<Script>) -> AppResult<(Vec<Script>, bool)> {
+    let settings = crate::settings::load(conn)?;
+    let speed = settings.scroll_speed(settings.prompter_mode);
+    let mut uncounted = false;
+    let scripts = scripts
+        .into_iter()
+        .map(|script| {
+            let mut script = state.db.vault.open_script(script)?;
+            match script.word_count {
+                Some(words) => script.estimated_seconds = Some(analysis::estimated_seconds(words, speed)),
+                None => uncounted = true,
+            }
+            Ok(script)
+        })
+        .collect::<AppResult<_>>()?;
+    Ok((scripts, uncounted))
+}
+
+/// Count, in the background, the scripts whose counts were cleared, such
+/// as by sync or by encrypting the library. Takes the database one script
+/// at a time and tells the library to reload once done.
+fn spawn_count_pending(app: &AppHandle) {
+    static RUNNING: AtomicBool = AtomicBool::new(false);
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let counted = app.try_state::<AppState>().map_or(0, |state| count_pending(&state));
+        RUNNING.store(false, Ordering::SeqCst);
+        if counted > 0 {
+            app.emit("library-changed", ()).ok();
+        }
+    });
+}
+
+/// Returns how many scripts were counted. A script that can't be opened,
+/// such as while the library is locked, is left for a later pass.
+fn count_pending(state: &AppState) -> usize {
+    if state.db.read_only {
+        return 0;
+    }
+    let ids = match state.db.conn.lock() {
+        Ok(conn) => conn
+            .prepare("SELECT id FROM scripts WHERE word_count IS NULL")
+            .and_then(|mut stmt| stmt.query_map([], |row| row.get(0))?.collect()),
+        Err(_) => return 0,
+    };
+    let ids: Vec<String> = ids.unwrap_or_default();
+
+    let count = |id: &str| -> AppResult<bool> {
+        let conn = state.db.conn.lock()?;
+        // Saved, and so counted, since the list was read
+        let Some(content) = conn
+            .query_row(
+                "SELECT content FROM scripts WHERE id = ?1 AND word_count IS NULL",
+                rusqlite::params![id],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?
+        else {
+            return Ok(false);
+        };
+        let content = state.db.vault.open(content)?;
+        let document = variables::filled_document(&conn, &state.db.vault, id, &content)?;
+        store_counts(state, &conn, id, &document)?;
+        Ok(true)
+    };
+    ids.iter().filter(|id| matches!(count(id), Ok(true))).count()
+}
+
+/// Keep a script's counts for the library. Stored whenever the script or
+/// its variables are saved; cleared by a trigger when its content changes
+/// any other way, until `count_pending` gets to it.
+fn store_counts(
+    state: &AppState,
+    conn: &Connection,
+    script_id: &str,
+    document: &Document,
+) -> AppResult<(u32, u32)> {
+    let counts = analysis::counts(document);
+    if !state.db.read_only {
+        conn.execute(
+            "UPDATE scripts SET word_count = ?1, line_count = ?2 WHERE id = ?3",
+            rusqlite::params![counts.0, counts.1, script_id],
+        )?;
+    }
+    Ok(counts)
+}
+
+/// Word count, duration and per-section pacing of a saved script, with its
+/// variables filled in, at the scroll speed of `mode` (by default the
+/// configured prompter mode).
+#[tauri::command]
+pub fn analyze_script(
+    state: State<AppState>,
+    script_id: String,
+    mode: Option<PrompterMode>,
+) -> AppResult<Analysis> {
+    let conn = state.db.conn.lock()?;
+    let content: String = conn
+        .query_row(
+            "SELECT content FROM scripts WHERE id = ?1",
+            rusqlite::params![script_id],
+            |row| row.get(0),
+        )
+        .map_err(AppError::script_lookup(&script_id))?;
+    let content = state.db.vault.open(content)?;
+    let document = variables::filled_document(&conn, &state.db.vault, &script_id, &content)?;
+    let settings = crate::settings::load(&conn)?;
+    store_counts(&state, &conn, &script_id, &document)?;
+    state.db.vault.touch();
+
+    let speed = settings.scroll_speed(mode.unwrap_or(settings.prompter_mode));
+    Ok(analysis::analyze(&document, speed))
+}
+
+/// The script's target duration in seconds, if it has one.
+pub fn target_seconds(conn: &Connection, script_id: &str) -> AppResult<Option<f64>> {
+    let result = conn
+        .query_row(
+            "SELECT target_seconds FROM script_targets WHERE script_id = ?1",
+            rusqlite::params![script_id],
+            |row| row.get(0),
+        )
+        .optional();
+    match result {
+        Ok(seconds) => Ok(seconds),
+        // Opened read-only from before the table existed
+        Err(_) if conn.is_readonly(DatabaseName::Main)? => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Make the prompter take `seconds` to scroll through the script, countdown
+/// excluded. `None` goes back to the configured speed.
+#[tauri::command]
+pub fn set_target_duration(
+    state: State<AppState>,
+    script_id: String,
+    seconds: Option<f64>,
+) -> AppResult<()> {
+    if let Some(seconds) = seconds {
+        if !seconds.is_finite() || !(1.0..=86_400.0).contains(&seconds) {
+            return Err(AppError::new(
+                ErrorCode::InvalidData,
+                "The target duration must be between 1 second and 24 hours",
+            ));
+        }
+    }
+    state.db.transaction(|tx| {
+        tx.query_row(
+            "SELECT 1 FROM scripts WHERE id = ?1",
+            rusqlite::params![script_id],
+            |_| Ok(()),
+        )
+        .map_err(AppError::script_lookup(&script_id))?;
+        match seconds {
+            Some(seconds) => tx.execute(
+                "INSERT INTO script_targets (script_id, target_seconds) VALUES (?1, ?2)
+                 ON CONFLICT(script_id) DO UPDATE SET target_seconds = excluded.target_seconds",
+                rusqlite::params![script_id, seconds],
+            )?,
+            None => tx.execute(
+                "DELETE FROM script_targets WHERE script_id = ?1",
+                rusqlite::params![script_id],
+            )?,
+        };
+        Ok(())
+    })
+}
+
+/// The scroll speed that meets the script's target duration at the font size
+/// of `mode` (by default the configured prompter mode), or `None` when it
+/// has no target.
+#[tauri::command]
+pub fn compute_target_speed(
+    state: State<AppState>,
+    script_id: String,
+    mode: Option<PrompterMode>,
+) -> AppResult<Option<TargetSpeed>> {
+    let conn = state.db.conn.lock()?;
+    let content: String = conn
+        .query_row(
+            "SELECT content FROM scripts WHERE id = ?1",
+            rusqlite::params![script_id],
+            |row| row.get(0),
+        )
+        .map_err(AppError::script_lookup(&script_id))?;
+    let Some(seconds) = target_seconds(&conn, &script_id)? else {
+        return Ok(None);
+    };
+    let content = state.db.vault.open(content)?;
+    let document = variables::filled_document(&conn, &state.db.vault, &script_id, &content)?;
+    let settings = crate::settings::load(&conn)?;
+    state.db.vault.touch();
+
+    let font_size = settings.font_size(mode.unwrap_or(settings.prompter_mode));
+    Ok(Some(analysis::target_speed(&document, seconds, font_size)))
+}
@@ -0,0 +1,27 @@
+use crate::error::AppResult;
+use crate::models::{Session, SessionStats};
+use crate::sessions;
+use crate::state::AppState;
+use tauri::State;
+
+/// Sessions stats are worked out from.
+const STATS_LIMIT: u32 = 500;
+
+/// A script's sessions, newest first.
+#[tauri::command]
+pub fn list_sessions(
+    state: State<AppState>,
+    script_id: String,
+    limit: Option<u32>,
+) -> AppResult<Vec<Session>> {
+    let conn = state.db.conn.lock()?;
+    sessions::list(&conn, &script_id, limit.unwrap_or(50))
+}
+
+/// Averages and trends over a script's recent sessions.
+#[tauri::command]
+pub fn get_session_stats(state: State<AppState>, script_id: String) -> AppResult<SessionStats> {
+    let conn = state.db.conn.lock()?;
+    let sessions = sessions::list(&conn, &script_id, STATS_LIMIT)?;
+    Ok(sessions::stats(&sessions))
+}
@@ -1,64 +1,195 @@
-use crate::models::Settings;
-use std::path::PathBuf;
+use crate::db::Database;
+use crate::error::AppResult;
+use crate::models::{Settings, SETTINGS_VERSION};
+use crate::profiles::Profiles;
+use crate::settings::{self, SettingChange, SettingSchema};
+use crate::settings_export::{self, ImportPreview};
+use crate::state::AppState;
+use rusqlite::Transaction;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::path::Path;
+use tauri::{AppHandle, Emitter, State};
 
-fn settings_path() -> PathBuf {
-    let base = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
-    base.join("Whispr").join("settings.json")
+/// Payload of the `settings-changed` event sent to every window.
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingsChanged {
+    pub settings: Settings,
+    pub changed: Vec<String>,
 }
 
 #[tauri::command]
-pub fn get_settings() -> Result<Settings, String> {
-    let path = settings_path();
-    if path.exists() {
-        let data = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
-        serde_json::from_str(&data).map_err(|e| e.to_string())
-    } else {
-        let settings = Settings::default();
-        save_settings(settings.clone())?;
-        Ok(settings)
-    }
+pub fn get_settings(state: State<AppState>) -> AppResult<Settings> {
+    // Loading may write: the one-time import, defaults on first run, migrations
+    let _guard = settings::write_lock();
+    settings::load(&*state.db.conn.lock()?)
 }
 
 #[tauri::command]
-pub fn save_settings(settings: Settings) -> Result<(), String> {
-    let path = settings_path();
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-    }
-    let data = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
-    std::fs::write(&path, data).map_err(|e| e.to_string())?;
+pub fn save_settings(app: AppHandle, state: State<AppState>, settings: Settings) -> AppResult<()> {
+    settings::validate(&settings)?;
+    let _guard = settings::write_lock();
+    let settings = Settings {
+        version: SETTINGS_VERSION,
+        ..settings
+    };
+    apply(&app, &state.db, "save", |_| Ok(settings))?;
     Ok(())
 }
 
+/// Change one setting. `value` may be any JSON value of the field's type.
+#[tauri::command]
+pub fn update_setting(
+    app: AppHandle,
+    state: State<AppState>,
+    key: String,
+    value: Value,
+) -> AppResult<Settings> {
+    update_settings(app, state, Map::from_iter([(key, value)]))
+}
+
+/// Change several settings at once; if any value is rejected, none are saved.
 #[tauri::command]
-pub fn update_setting(key: String, value: String) -> Result<Settings, String> {
-    let mut settings = get_settings()?;
+pub fn update_settings(
+    app: AppHandle,
+    state: State<AppState>,
+    changes: Map<String, Value>,
+) -> AppResult<Settings> {
+    let _guard = settings::write_lock();
+    apply(&app, &state.db, "update", |previous| settings::patch(previous, &changes))
+}
 
-    match key.as_str() {
-        "notch_font_size" => settings.notch_font_size = value.parse().map_err(|e: std::num::ParseFloatError| e.to_string())?,
-        "notch_scroll_speed" => settings.notch_scroll_speed = value.parse().map_err(|e: std::num::ParseFloatError| e.to_string())?,
-        "notch_width_percent" => settings.notch_width_percent = value.parse().map_err(|e: std::num::ParseFloatError| e.to_string())?,
-        "notch_height" => settings.notch_height = value.parse().map_err(|e: std::num::ParseFloatError| e.to_string())?,
-        "notch_font_family" => settings.notch_font_family = value,
-        "notch_opacity" => settings.notch_opacity = value.parse().map_err(|e: std::num::ParseFloatError| e.to_string())?,
-        "notch_glow_style" => settings.notch_glow_style = value,
-        "notch_position" => settings.notch_position = value,
-        "notch_line_count" => settings.notch_line_count = value.parse().map_err(|e: std::num::ParseIntError| e.to_string())?,
-        "notch_show_timer" => settings.notch_show_timer = value.parse().map_err(|e: std::str::ParseBoolError| e.to_string())?,
-        "floating_font_size" => settings.floating_font_size = value.parse().map_err(|e: std::num::ParseFloatError| e.to_string())?,
-        "floating_scroll_speed" => settings.floating_scroll_speed = value.parse().map_err(|e: std::num::ParseFloatError| e.to_string())?,
-        "floating_font_family" => settings.floating_font_family = value,
-        "floating_theme" => settings.floating_theme = value,
-        "text_color_hex" => settings.text_color_hex = value,
-        "mirror_mode" => settings.mirror_mode = value.parse().map_err(|e: std::str::ParseBoolError| e.to_string())?,
-        "countdown_seconds" => settings.countdown_seconds = value.parse().map_err(|e: std::num::ParseIntError| e.to_string())?,
-        "prompter_mode" => settings.prompter_mode = value,
-        "show_menu_bar_icon" => settings.show_menu_bar_icon = value.parse().map_err(|e: std::str::ParseBoolError| e.to_string())?,
-        "appearance_mode" => settings.appearance_mode = value,
-        "end_action" => settings.end_action = value,
-        _ => return Err(format!("Unknown setting key: {}", key)),
-    }
+/// Recent settings changes, newest first, optionally for one key.
+#[tauri::command]
+pub fn get_settings_history(
+    state: State<AppState>,
+    key: Option<String>,
+    limit: Option<u32>,
+) -> AppResult<Vec<SettingChange>> {
+    let conn = state.db.conn.lock()?;
+    settings::history(&conn, key.as_deref(), limit.unwrap_or(100))
+}
+
+/// Type, range and allowed values of every setting, for building the settings UI.
+#[tauri::command]
+pub fn get_settings_schema() -> Vec<SettingSchema> {
+    settings::schema()
+}
+
+/// Write the current settings, or a profile's, to a file for someone else.
+#[tauri::command]
+pub fn export_settings(
+    state: State<AppState>,
+    path: String,
+    profile_id: Option<String>,
+) -> AppResult<()> {
+    let _guard = settings::write_lock();
+    let conn = state.db.conn.lock()?;
+    let profiles = Profiles::load(&conn)?;
+    let (name, settings) = match profile_id {
+        Some(id) => {
+            let profile = profiles.get(&id)?;
+            (Some(profile.name.clone()), profile.settings.clone())
+        }
+        None => {
+            let active = profiles.active.as_deref().and_then(|id| profiles.get(id).ok());
+            (active.map(|p| p.name.clone()), settings::load(&conn)?)
+        }
+    };
+    settings_export::export(Path::new(&path), name, &settings)
+}
 
-    save_settings(settings.clone())?;
+/// What importing `path` would change, limited to `groups` when given.
+#[tauri::command]
+pub fn preview_settings_import(
+    state: State<AppState>,
+    path: String,
+    groups: Option<Vec<String>>,
+) -> AppResult<ImportPreview> {
+    let file = settings_export::read(Path::new(&path))?;
+    let current = get_settings(state)?;
+    let (_, preview) = settings_export::merge(&current, &file, groups.as_deref())?;
+    Ok(preview)
+}
+
+/// Apply a settings file. Nothing is changed if any imported value is invalid.
+#[tauri::command]
+pub fn import_settings(
+    app: AppHandle,
+    state: State<AppState>,
+    path: String,
+    groups: Option<Vec<String>>,
+) -> AppResult<Settings> {
+    let file = settings_export::read(Path::new(&path))?;
+    let _guard = settings::write_lock();
+    apply(&app, &state.db, "import", |previous| {
+        settings_export::merge(previous, &file, groups.as_deref()).map(|(settings, _)| settings)
+    })
+}
+
+/// Make the result of `change` the live settings: store it, keep the
+/// active profile in step and tell every window. Callers hold
+/// `settings::write_lock()`.
+pub(crate) fn apply(
+    app: &AppHandle,
+    db: &Database,
+    source: &str,
+    change: impl FnOnce(&Settings) -> AppResult<Settings>,
+) -> AppResult<Settings> {
+    let (previous, settings) = store_live(app, db, source, |tx, previous| {
+        let settings = change(previous)?;
+        let mut profiles = Profiles::load(tx)?;
+        if profiles.store_active(&settings) {
+            profiles.save(tx)?;
+        }
+        Ok(settings)
+    })?;
+    broadcast(app, &previous, settings.clone());
     Ok(settings)
 }
+
+/// Store the result of `change` in one transaction with the read it was
+/// based on; `change` may write to the same transaction. Returns the
+/// previous settings and the new ones.
+///
+/// The hold key is registered once the transaction has committed. If the
+/// key turns out to be taken, the settings and profiles are put back as
+/// they were and the error returned, as if the change had been rejected.
+pub(crate) fn store_live(
+    app: &AppHandle,
+    db: &Database,
+    source: &str,
+    change: impl FnOnce(&Transaction, &Settings) -> AppResult<Settings>,
+) -> AppResult<(Settings, Settings)> {
+    let (previous, settings, profiles) = db.transaction(|tx| {
+        let previous = settings::load(tx)?;
+        let profiles = Profiles::load(tx)?;
+        let settings = change(tx, &previous)?;
+        settings::store(tx, Some(&previous), &settings, source)?;
+        Ok((previous, settings, profiles))
+    })?;
+
+    if let Err(e) = crate::shortcuts::apply_hold(app, &settings) {
+        db.transaction(|tx| {
+            settings::store(tx, Some(&settings), &previous, source)?;
+            profiles.save(tx)
+        })?;
+        crate::shortcuts::apply_hold(app, &previous).ok();
+        return Err(e);
+    }
+    Ok((previous, settings))
+}
+
+/// Tell every window what changed, and move the top bar if its geometry did.
+pub(crate) fn broadcast(app: &AppHandle, previous: &Settings, settings: Settings) {
+    let changed = settings::changed_keys(previous, &settings);
+    if changed.is_empty() {
+        return;
+    }
+
+    let geometry = ["notch_width_percent", "notch_height", "notch_position"];
+    if changed.iter().any(|key| geometry.contains(&key.as_str())) {
+        crate::commands::window::layout_topbar(app, &settings).ok();
+    }
+    app.emit("settings-changed", SettingsChanged { settings, changed }).ok();
+}
@@ -0,0 +1,110 @@
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+use crate::sync::folder::{self, FolderSyncStatus, SyncReport};
+use crate::sync::webdav::{self, WebDavConfig, WebDavReport, WebDavStatus};
+use crate::sync::SyncConfig;
+use std::path::PathBuf;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn get_folder_sync(state: State<AppState>) -> AppResult<FolderSyncStatus> {
+    Ok(state.folder_sync.status())
+}
+
+/// Choose the folder to mirror the library into, or `None` to turn sync off.
+#[tauri::command]
+pub fn set_folder_sync(
+    app: AppHandle,
+    state: State<AppState>,
+    folder: Option<String>,
+) -> AppResult<FolderSyncStatus> {
+    state.db.ensure_writable()?;
+    if folder.is_some() {
+        crate::sync::ensure_unencrypted(&state.db.vault).map_err(AppError::sync)?;
+    }
+
+    let mut config = SyncConfig::load();
+    // Sync state describes the previous folder; carrying it over would read
+    // every missing file as a deletion
+    if config.folder != folder {
+        let conn = state.db.conn.lock()?;
+        conn.execute("DELETE FROM folder_sync_state", [])?;
+    }
+
+    match &folder {
+        Some(dir) => {
+            let dir = PathBuf::from(dir);
+            std::fs::create_dir_all(&dir)?;
+            state.folder_sync.start(&app, dir).map_err(AppError::sync)?;
+        }
+        None => state.folder_sync.stop(),
+    }
+
+    config.folder = folder;
+    config.save().map_err(AppError::sync)?;
+
+    Ok(state.folder_sync.status())
+}
+
+#[tauri::command]
+pub fn sync_folder_now(app: AppHandle) -> AppResult<SyncReport> {
+    folder::sync_now(&app).map_err(AppError::sync)
+}
+
+#[tauri::command]
+pub fn get_webdav_sync(state: State<AppState>) -> AppResult<WebDavStatus> {
+    Ok(state.webdav_sync.status())
+}
+
+/// Configure the WebDAV server, or `None` to turn WebDAV sync off.
+/// Leaving `password` empty keeps the stored one.
+#[tauri::command]
+pub fn set_webdav_sync(
+    app: AppHandle,
+    state: State<AppState>,
+    config: Option<WebDavConfig>,
+) -> AppResult<WebDavStatus> {
+    if config.is_some() {
+        crate::sync::ensure_unencrypted(&state.db.vault).map_err(AppError::sync)?;
+    }
+    let mut sync_config = SyncConfig::load();
+    let previous = sync_config.webdav.take();
+    let config = match config {
+        Some(mut c) => {
+            c.validate().map_err(AppError::sync)?;
+            if c.password.is_empty() {
+                if let Some(previous) = previous.as_ref().filter(|p| p.url == c.url) {
+                    c.password = previous.with_password().map_err(AppError::sync)?.password;
+                }
+            }
+            c.store_password().map_err(AppError::sync)?;
+            Some(c)
+        }
+        None => None,
+    };
+    if let Some(previous) = &previous {
+        let same_entry = config
+            .as_ref()
+            .is_some_and(|c| c.url == previous.url && c.username == previous.username);
+        if !same_entry {
+            previous.forget_password();
+        }
+    }
+
+    // Versions recorded against another server mean nothing to this one
+    if previous.as_ref().map(|c| &c.url) != config.as_ref().map(|c| &c.url) {
+        let conn = state.db.conn.lock()?;
+        conn.execute("DELETE FROM webdav_sync_state", [])?;
+    }
+
+    sync_config.webdav = config;
+    sync_config.save().map_err(AppError::sync)?;
+    state.webdav_sync.configure(&app, sync_config.webdav.as_ref());
+
+    Ok(state.webdav_sync.status())
+}
+
+#[tauri::command]
+pub fn sync_webdav_now(app: AppHandle) -> AppResult<WebDavReport> {
+    webdav::sync_now(&app).map_err(AppError::sync)
+}
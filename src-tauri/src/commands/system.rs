@@ -1,11 +1,14 @@
+use crate::error::AppResult;
+use crate::paths::{self, DataLocation};
+
 /// Read file content from a given path (for file import).
 /// Falls back to lossy UTF-8 conversion if strict parsing fails.
 #[tauri::command]
-pub fn read_file_content(path: String) -> Result<String, String> {
+pub fn read_file_content(path: String) -> AppResult<String> {
     match std::fs::read_to_string(&path) {
         Ok(content) => Ok(content),
         Err(_) => {
-            let bytes = std::fs::read(&path).map_err(|e| e.to_string())?;
+            let bytes = std::fs::read(&path)?;
             Ok(String::from_utf8_lossy(&bytes).into_owned())
         }
     }
@@ -14,7 +17,7 @@ pub fn read_file_content(path: String) -> Result<String, String> {
 /// Prevent the system from going to sleep while prompter is active.
 /// On Windows: SetThreadExecutionState. On macOS: IOKit (handled in Swift).
 #[tauri::command]
-pub fn prevent_sleep(prevent: bool) -> Result<(), String> {
+pub fn prevent_sleep(prevent: bool) -> AppResult<()> {
     #[cfg(target_os = "windows")]
     {
         use std::os::raw::c_ulong;
@@ -43,3 +46,9 @@ pub fn prevent_sleep(prevent: bool) -> Result<(), String> {
 
     Ok(())
 }
+
+/// Where the library and settings are stored, and why.
+#[tauri::command]
+pub fn get_data_location() -> DataLocation {
+    paths::location().clone()
+}
@@ -0,0 +1,85 @@
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::state::AppState;
+use crate::sync::SyncConfig;
+use crate::vault::{self, VaultStatus};
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn get_vault_status(state: State<AppState>) -> AppResult<VaultStatus> {
+    Ok(state.db.vault.status())
+}
+
+#[tauri::command]
+pub fn unlock_library(app: AppHandle, state: State<AppState>, passphrase: String) -> AppResult<()> {
+    {
+        let conn = state.db.conn.lock()?;
+        state.db.vault.unlock(&conn, &passphrase)?;
+    }
+    vault::finish_unlock(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn lock_library(app: AppHandle, state: State<AppState>) -> AppResult<()> {
+    if !state.db.vault.status().enabled {
+        return Err(AppError::new(ErrorCode::Encryption, "Encryption is not enabled"));
+    }
+    vault::lock_now(&app);
+    Ok(())
+}
+
+/// Encrypt the library. The backup snapshot is refreshed so no plaintext
+/// copy is left behind, and sync has to be off for the same reason.
+#[tauri::command]
+pub fn enable_encryption(app: AppHandle, state: State<AppState>, passphrase: String) -> AppResult<VaultStatus> {
+    state.db.ensure_writable()?;
+    let sync = SyncConfig::load();
+    if sync.folder.is_some() || sync.webdav.is_some() {
+        return Err(AppError::new(
+            ErrorCode::Encryption,
+            "Turn off folder and WebDAV sync before encrypting the library",
+        ));
+    }
+    {
+        let mut conn = state.db.conn.lock()?;
+        state.db.vault.enable(&mut conn, &passphrase)?;
+    }
+    state.db.write_backup();
+    crate::tray::refresh(&app);
+    Ok(state.db.vault.status())
+}
+
+#[tauri::command]
+pub fn disable_encryption(app: AppHandle, state: State<AppState>, passphrase: String) -> AppResult<VaultStatus> {
+    state.db.ensure_writable()?;
+    {
+        let mut conn = state.db.conn.lock()?;
+        state.db.vault.disable(&mut conn, &passphrase)?;
+    }
+    state.db.write_backup();
+    crate::tray::refresh(&app);
+    Ok(state.db.vault.status())
+}
+
+#[tauri::command]
+pub fn change_passphrase(state: State<AppState>, current: String, new_passphrase: String) -> AppResult<()> {
+    state.db.ensure_writable()?;
+    {
+        let conn = state.db.conn.lock()?;
+        state.db.vault.change_passphrase(&conn, &current, &new_passphrase)?;
+    }
+    // The backup holds the data key wrapped with the old passphrase
+    state.db.write_backup();
+    Ok(())
+}
+
+/// Lock after `minutes` without activity, or never when `None`.
+#[tauri::command]
+pub fn set_auto_lock(state: State<AppState>, minutes: Option<u32>) -> AppResult<VaultStatus> {
+    state.db.ensure_writable()?;
+    {
+        let conn = state.db.conn.lock()?;
+        state.db.vault.set_auto_lock(&conn, minutes)?;
+    }
+    Ok(state.db.vault.status())
+}
@@ -1,99 +1,156 @@
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::markup::Document;
+use crate::models::{EndAction, NotchPosition, PrompterMode, PrompterPhase, PrompterStatus, Settings};
+use crate::sessions::ActiveSession;
 use crate::state::AppState;
-use tauri::{AppHandle, Emitter, Manager, State, WebviewWindowBuilder, WebviewUrl};
+use tauri::{
+    AppHandle, Emitter, LogicalPosition, LogicalSize, Manager, State, WebviewUrl, WebviewWindow,
+    WebviewWindowBuilder,
+};
+
+/// What a run list item changes about how its script is prompted.
+#[derive(Debug, Clone, Default)]
+pub struct PromptOverrides {
+    pub speed: Option<f64>,
+    pub end_action: Option<EndAction>,
+}
 
 #[tauri::command]
 pub fn open_topbar_prompter(
     app: AppHandle,
     state: State<AppState>,
     script_id: String,
-) -> Result<(), String> {
-    // Close existing if any
-    if let Some(w) = app.get_webview_window("topbar-prompter") {
-        w.close().map_err(|e: tauri::Error| e.to_string())?;
+    allow_unresolved: Option<bool>,
+) -> AppResult<()> {
+    // Prompting a script on its own leaves the run list
+    crate::commands::run_lists::end_run(&app, &state)?;
+    open_topbar(&app, &state, &script_id, allow_unresolved.unwrap_or(false), &PromptOverrides::default())
+}
+
+#[tauri::command]
+pub fn open_floating_prompter(
+    app: AppHandle,
+    state: State<AppState>,
+    script_id: String,
+    allow_unresolved: Option<bool>,
+) -> AppResult<()> {
+    // Prompting a script on its own leaves the run list
+    crate::commands::run_lists::end_run(&app, &state)?;
+    open_floating(&app, &state, &script_id, allow_unresolved.unwrap_or(false), &PromptOverrides::default())
+}
+
+/// Open `script_id` in the prompter `mode`, closing the other kind so only
+/// one prompter shows at a time.
+pub fn open(
+    app: &AppHandle,
+    state: &AppState,
+    script_id: &str,
+    mode: PrompterMode,
+    allow_unresolved: bool,
+    overrides: &PromptOverrides,
+) -> AppResult<()> {
+    let other = match mode {
+        PrompterMode::Notch => "floating-prompter",
+        PrompterMode::Floating => "topbar-prompter",
+    };
+    if let Some(w) = app.get_webview_window(other) {
+        w.close()?;
+    }
+    match mode {
+        PrompterMode::Notch => open_topbar(app, state, script_id, allow_unresolved, overrides),
+        PrompterMode::Floating => open_floating(app, state, script_id, allow_unresolved, overrides),
     }
+}
 
-    // Get the script content
-    let conn = state.db.conn.lock().map_err(|e| e.to_string())?;
-    let content: String = conn
-        .query_row(
-            "SELECT content FROM scripts WHERE id = ?1",
-            rusqlite::params![script_id],
-            |row| row.get(0),
-        )
-        .map_err(|e| e.to_string())?;
-    drop(conn);
+fn open_topbar(
+    app: &AppHandle,
+    state: &AppState,
+    script_id: &str,
+    allow_unresolved: bool,
+    overrides: &PromptOverrides,
+) -> AppResult<()> {
+    let (title, document, settings, speed) =
+        load_script(state, script_id, PrompterMode::Notch, allow_unresolved, overrides.speed)?;
 
-    let monitor = app
-        .primary_monitor()
-        .map_err(|e: tauri::Error| e.to_string())?
-        .ok_or("No primary monitor found")?;
-    let screen_width = monitor.size().width as f64 / monitor.scale_factor();
+    // Close existing if any
+    if let Some(w) = app.get_webview_window("topbar-prompter") {
+        w.close()?;
+    }
+    start_status(app, state, script_id, title.clone(), PrompterMode::Notch, &document, speed)?;
 
-    // Read settings for window dimensions
-    let settings = crate::commands::settings::get_settings()?;
-    let width_pct = settings.notch_width_percent / 100.0;
-    let win_width = screen_width * width_pct;
-    let win_height = settings.notch_height;
-    let win_x = (screen_width - win_width) / 2.0;
+    let (win_x, win_y, win_width, win_height) = topbar_geometry(app, &settings)?;
 
     let win = WebviewWindowBuilder::new(
-        &app,
+        app,
         "topbar-prompter",
         WebviewUrl::App("prompter-topbar.html".into()),
     )
-    .title("Whispr Prompter")
+    .title(prompter_title(&title))
     .inner_size(win_width, win_height)
-    .position(win_x, 0.0)
+    .position(win_x, win_y)
     .decorations(false)
     .always_on_top(true)
     .resizable(false)
     .skip_taskbar(true)
     .content_protected(true)
-    .build()
-    .map_err(|e: tauri::Error| e.to_string())?;
+    .build()?;
 
-    // Send script content after a short delay for the window to initialize
-    let content_clone = content.clone();
-    let script_id_clone = script_id.clone();
-    std::thread::spawn(move || {
-        std::thread::sleep(std::time::Duration::from_millis(500));
-        win.emit("load-script", serde_json::json!({
-            "id": script_id_clone,
-            "content": content_clone,
-        }))
-        .ok();
-    });
+    send_script(win, script_id, document, speed, overrides.end_action);
 
     Ok(())
 }
 
-#[tauri::command]
-pub fn open_floating_prompter(
-    app: AppHandle,
-    state: State<AppState>,
-    script_id: String,
-) -> Result<(), String> {
+/// Position and size of the top bar: centered, `notch_width_percent` of the
+/// screen wide, along the top edge or the bottom one.
+fn topbar_geometry(app: &AppHandle, settings: &Settings) -> AppResult<(f64, f64, f64, f64)> {
+    let monitor = app
+        .primary_monitor()?
+        .ok_or_else(|| AppError::new(ErrorCode::Window, "No primary monitor found"))?;
+    let screen_width = monitor.size().width as f64 / monitor.scale_factor();
+    let screen_height = monitor.size().height as f64 / monitor.scale_factor();
+
+    let width = screen_width * settings.notch_width_percent / 100.0;
+    let height = settings.notch_height;
+    let x = (screen_width - width) / 2.0;
+    let y = match settings.notch_position {
+        NotchPosition::Top => 0.0,
+        NotchPosition::Bottom => screen_height - height,
+    };
+    Ok((x, y, width, height))
+}
+
+/// Apply changed geometry settings to an open top bar.
+pub fn layout_topbar(app: &AppHandle, settings: &Settings) -> AppResult<()> {
+    let Some(win) = app.get_webview_window("topbar-prompter") else {
+        return Ok(());
+    };
+    let (x, y, width, height) = topbar_geometry(app, settings)?;
+    win.set_size(LogicalSize::new(width, height))?;
+    win.set_position(LogicalPosition::new(x, y))?;
+    Ok(())
+}
+
+fn open_floating(
+    app: &AppHandle,
+    state: &AppState,
+    script_id: &str,
+    allow_unresolved: bool,
+    overrides: &PromptOverrides,
+) -> AppResult<()> {
+    let (title, document, _, speed) =
+        load_script(state, script_id, PrompterMode::Floating, allow_unresolved, overrides.speed)?;
+
     if let Some(w) = app.get_webview_window("floating-prompter") {
-        w.close().map_err(|e: tauri::Error| e.to_string())?;
+        w.close()?;
     }
-
-    // Get the script content
-    let conn = state.db.conn.lock().map_err(|e| e.to_string())?;
-    let content: String = conn
-        .query_row(
-            "SELECT content FROM scripts WHERE id = ?1",
-            rusqlite::params![script_id],
-            |row| row.get(0),
-        )
-        .map_err(|e| e.to_string())?;
-    drop(conn);
+    start_status(app, state, script_id, title.clone(), PrompterMode::Floating, &document, speed)?;
 
     let win = WebviewWindowBuilder::new(
-        &app,
+        app,
         "floating-prompter",
         WebviewUrl::App("prompter-floating.html".into()),
     )
-    .title("Whispr Prompter")
+    .title(prompter_title(&title))
     .inner_size(480.0, 320.0)
     .min_inner_size(400.0, 200.0)
     .max_inner_size(1400.0, 800.0)
@@ -103,38 +160,183 @@ pub fn open_floating_prompter(
     .resizable(true)
     .skip_taskbar(true)
     .content_protected(true)
-    .build()
-    .map_err(|e: tauri::Error| e.to_string())?;
+    .build()?;
+
+    send_script(win, script_id, document, speed, overrides.end_action);
+
+    Ok(())
+}
+
+/// Title, document, settings and scroll speed for a prompter about to open.
+/// Stored variables are filled in; placeholders still without a value refuse
+/// the start unless `allow_unresolved`, in which case the prompter highlights
+/// them. A script with a target duration gets the speed that meets it,
+/// unless `speed` is given.
+fn load_script(
+    state: &AppState,
+    script_id: &str,
+    mode: PrompterMode,
+    allow_unresolved: bool,
+    speed: Option<f64>,
+) -> AppResult<(String, Document, Settings, f64)> {
+    let conn = state.db.conn.lock()?;
+    let (title, content): (String, String) = conn
+        .query_row(
+            "SELECT title, content FROM scripts WHERE id = ?1",
+            rusqlite::params![script_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(AppError::script_lookup(script_id))?;
+    let settings = crate::settings::load(&conn)?;
+    let content = state.db.vault.open(content)?;
+    let document = crate::variables::filled_document(&conn, &state.db.vault, script_id, &content)?;
+    let target = crate::commands::scripts::target_seconds(&conn, script_id)?;
+    drop(conn);
+
+    let unresolved = document.placeholders();
+    if !unresolved.is_empty() && !allow_unresolved {
+        return Err(AppError::unresolved_placeholders(unresolved));
+    }
+    let speed = match (speed, target) {
+        (Some(speed), _) => speed,
+        (None, Some(seconds)) => {
+            let target = crate::analysis::target_speed(&document, seconds, settings.font_size(mode));
+            target.speed
+        }
+        (None, None) => settings.scroll_speed(mode),
+    };
+    Ok((title, document, settings, speed))
+}
 
-    let content_clone = content.clone();
-    let script_id_clone = script_id.clone();
+/// Send the parsed script after a short delay for the window to initialize.
+/// `end_action`, when given, replaces the one in the settings.
+fn send_script(
+    win: WebviewWindow,
+    script_id: &str,
+    document: Document,
+    speed: f64,
+    end_action: Option<EndAction>,
+) {
+    let script_id = script_id.to_string();
     std::thread::spawn(move || {
         std::thread::sleep(std::time::Duration::from_millis(500));
         win.emit("load-script", serde_json::json!({
-            "id": script_id_clone,
-            "content": content_clone,
+            "id": script_id,
+            "document": document,
+            "speed": speed,
+            "end_action": end_action,
         }))
         .ok();
     });
+}
+
+/// A new prompter counts down first; from then on it reports its own phase
+/// and the cues it passes. Its session starts now, ending any the last
+/// prompter left open.
+fn start_status(
+    app: &AppHandle,
+    state: &AppState,
+    script_id: &str,
+    title: String,
+    mode: PrompterMode,
+    document: &Document,
+    speed: f64,
+) -> AppResult<()> {
+    *state.prompter.lock()? = PrompterStatus {
+        phase: PrompterPhase::CountingDown,
+        script_id: Some(script_id.to_string()),
+        title,
+        progress: 0.0,
+        speed,
+    };
+    state.cues.load(script_id, document)?;
+    end_session(state)?;
+    let (word_count, _) = crate::analysis::counts(document);
+    let session = ActiveSession::start(script_id, mode, word_count, speed);
+    if !state.db.read_only {
+        session.save(&*state.db.conn.lock()?, false)?;
+    }
+    *state.session.lock()? = Some(session);
+    crate::tray::refresh(app);
+    Ok(())
+}
 
+fn end_session(state: &AppState) -> AppResult<()> {
+    let session = state.session.lock()?.take();
+    if let Some(session) = session {
+        if !state.db.read_only {
+            session.save(&*state.db.conn.lock()?, true)?;
+        }
+    }
     Ok(())
 }
 
+fn prompter_title(script_title: &str) -> String {
+    format!("{} — Whispr Prompter", crate::text::truncate(script_title, 40))
+}
+
 #[tauri::command]
-pub fn close_prompter(app: AppHandle) -> Result<(), String> {
+pub fn close_prompter(app: AppHandle) -> AppResult<()> {
+    if let Some(state) = app.try_state::<AppState>() {
+        *state.prompter.lock()? = PrompterStatus::default();
+    }
     if let Some(w) = app.get_webview_window("topbar-prompter") {
-        w.close().map_err(|e: tauri::Error| e.to_string())?;
+        w.close()?;
     }
     if let Some(w) = app.get_webview_window("floating-prompter") {
-        w.close().map_err(|e: tauri::Error| e.to_string())?;
+        w.close()?;
+    }
+    crate::tray::refresh(&app);
+    if let Some(state) = app.try_state::<AppState>() {
+        state.cues.clear()?;
+        end_session(&state)?;
+        crate::commands::run_lists::end_run(&app, &state)?;
+    }
+    Ok(())
+}
+
+/// Called by the prompter as it starts, pauses, finishes, changes speed or
+/// moves on. The menu is only rebuilt when its controls change; progress
+/// alone just updates the tooltip. Finishing moves a running run list on.
+#[tauri::command]
+pub fn report_prompter_status(
+    app: AppHandle,
+    state: State<AppState>,
+    phase: PrompterPhase,
+    progress: f64,
+    speed: f64,
+) -> AppResult<()> {
+    let (controls_changed, finished) = {
+        let mut status = state.prompter.lock()?;
+        let changed = status.phase != phase || status.speed != speed;
+        let finished = phase == PrompterPhase::Finished && status.phase != PrompterPhase::Finished;
+        status.phase = phase;
+        status.progress = progress.clamp(0.0, 1.0);
+        status.speed = speed;
+        (changed, finished)
+    };
+    let mut session = state.session.lock()?;
+    if let Some(session) = session.as_mut() {
+        if session.report(phase, progress, speed) && !state.db.read_only {
+            session.save(&*state.db.conn.lock()?, false)?;
+        }
+    }
+    drop(session);
+    if controls_changed {
+        crate::tray::refresh(&app);
+    } else {
+        crate::tray::show_status(&app);
+    }
+    if finished {
+        crate::commands::run_lists::script_finished(&app, &state)?;
     }
     Ok(())
 }
 
 #[tauri::command]
-pub fn open_settings_window(app: AppHandle) -> Result<(), String> {
+pub fn open_settings_window(app: AppHandle) -> AppResult<()> {
     if let Some(w) = app.get_webview_window("settings") {
-        w.set_focus().map_err(|e: tauri::Error| e.to_string())?;
+        w.set_focus()?;
         return Ok(());
     }
 
@@ -143,8 +345,7 @@ pub fn open_settings_window(app: AppHandle) -> Result<(), String> {
         .inner_size(640.0, 520.0)
         .center()
         .resizable(false)
-        .build()
-        .map_err(|e: tauri::Error| e.to_string())?;
+        .build()?;
 
     Ok(())
 }
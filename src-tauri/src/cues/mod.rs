@@ -0,0 +1,204 @@
+//! Cue dispatch. As the prompter scrolls onto a word with cues it reports
+//! each one by its position in `Document::cues`; the dispatcher looks the
+//! cue up in the document it sent, emits `cue-fired` to every window and
+//! fires the outputs configured for that cue, each on its own thread. An
+//! output that fails emits `cue-output-failed`.
+//!
+//! Outputs name local programs and network addresses, so like sync they are
+//! machine-specific: kept in `cues.json` rather than `Settings`.
+
+pub mod output;
+
+pub use output::Output;
+
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::markup::{Document, Inline};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CueOutput {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    /// Cue names this output fires for, in any case; empty for every cue
+    #[serde(default)]
+    pub cues: Vec<String>,
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub output: Output,
+}
+
+impl CueOutput {
+    pub fn matches(&self, cue: &str) -> bool {
+        self.cues.is_empty() || self.cues.iter().any(|c| c.trim().eq_ignore_ascii_case(cue))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Give the output a name".into());
+        }
+        self.output.validate()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CueConfig {
+    pub outputs: Vec<CueOutput>,
+}
+
+impl CueConfig {
+    fn path() -> PathBuf {
+        crate::paths::data_file("cues.json")
+    }
+
+    /// A missing file means no outputs. One that can't be read is an
+    /// error rather than empty, so the next save doesn't replace it.
+    pub fn load() -> Result<Self, String> {
+        Self::read(&Self::path())
+    }
+
+    fn read(path: &Path) -> Result<Self, String> {
+        let data = match std::fs::read_to_string(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("Couldn't read {}: {}", path.display(), e)),
+        };
+        serde_json::from_str(&data)
+            .map_err(|e| format!("{} is damaged; fix or remove it to set up cue outputs: {}", path.display(), e))
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let data = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        crate::files::write_atomic(&Self::path(), data.as_bytes()).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+/// A cue the prompter passed, as emitted and as sent to webhooks.
+#[derive(Debug, Clone, Serialize)]
+pub struct CueEvent {
+    pub script_id: String,
+    /// Position among the script's cues
+    pub index: usize,
+    pub name: String,
+    pub argument: Option<String>,
+    pub line: usize,
+    pub fired_at: String,
+}
+
+impl CueEvent {
+    /// What `test_cue_output` sends.
+    pub fn sample(output: &CueOutput) -> Self {
+        Self {
+            script_id: String::new(),
+            index: 0,
+            name: output.cues.first().map_or("test", |c| c.trim()).to_string(),
+            argument: None,
+            line: 0,
+            fired_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct CueFailure {
+    output_id: String,
+    output_name: String,
+    cue: String,
+    message: String,
+}
+
+struct Marker {
+    name: String,
+    argument: Option<String>,
+    line: usize,
+}
+
+/// The open prompter's cues.
+#[derive(Default)]
+pub struct CueDispatcher {
+    active: Mutex<Option<(String, Vec<Marker>)>>,
+}
+
+impl CueDispatcher {
+    /// Hold the cues of the document a prompter is about to show.
+    pub fn load(&self, script_id: &str, document: &Document) -> AppResult<()> {
+        let markers = document
+            .cues()
+            .filter_map(|inline| match inline {
+                Inline::Cue { name, argument, line } => Some(Marker {
+                    name: name.clone(),
+                    argument: argument.clone(),
+                    line: *line,
+                }),
+                _ => None,
+            })
+            .collect();
+        *self.active.lock()? = Some((script_id.to_string(), markers));
+        Ok(())
+    }
+
+    pub fn clear(&self) -> AppResult<()> {
+        *self.active.lock()? = None;
+        Ok(())
+    }
+
+    /// Announce the open script's cue at `index` and fire its outputs.
+    pub fn dispatch(&self, app: &AppHandle, index: usize) -> AppResult<CueEvent> {
+        let event = {
+            let active = self.active.lock()?;
+            let (script_id, marker) = active
+                .as_ref()
+                .and_then(|(script_id, markers)| Some((script_id, markers.get(index)?)))
+                .ok_or_else(|| AppError::new(ErrorCode::NotFound, "No such cue in the open script"))?;
+            CueEvent {
+                script_id: script_id.clone(),
+                index,
+                name: marker.name.clone(),
+                argument: marker.argument.clone(),
+                line: marker.line,
+                fired_at: chrono::Utc::now().to_rfc3339(),
+            }
+        };
+        app.emit("cue-fired", &event).ok();
+
+        let outputs = CueConfig::load().map_err(AppError::cue_output)?.outputs;
+        for output in outputs.into_iter().filter(|o| o.enabled && o.matches(&event.name)) {
+            let app = app.clone();
+            let event = event.clone();
+            std::thread::spawn(move || {
+                if let Err(message) = output.output.fire(&event) {
+                    let failure = CueFailure {
+                        output_id: output.id,
+                        output_name: output.name,
+                        cue: event.name,
+                        message,
+                    };
+                    app.emit("cue-output-failed", failure).ok();
+                }
+            });
+        }
+        Ok(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_damaged_config_is_an_error_not_empty() {
+        let path = std::env::temp_dir().join(format!("whispr-cues-{}.json", uuid::Uuid::new_v4()));
+        assert!(CueConfig::read(&path).unwrap().outputs.is_empty());
+
+        std::fs::write(&path, "{ \"outputs\": [").unwrap();
+        let result = CueConfig::read(&path);
+        std::fs::remove_file(&path).ok();
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,310 @@
+//! The things a cue can trigger: an HTTP webhook, an OSC message over UDP or
+//! a local program.
+
+use super::CueEvent;
+use serde::{Deserialize, Serialize};
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// How long a webhook may take, and how long a program is watched for a
+/// failing exit before it's left to run.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Output {
+    /// POST the event as JSON.
+    Webhook { url: String },
+    /// One OSC message, to `/whispr/cue/<name>` unless `address` is given,
+    /// carrying the cue's argument if it has one.
+    Osc {
+        host: String,
+        port: u16,
+        #[serde(default)]
+        address: Option<String>,
+    },
+    /// Run a program directly, without a shell. `{cue}`, `{argument}`,
+    /// `{line}` and `{script_id}` in its arguments are filled in.
+    Command {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+impl Output {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Output::Webhook { url } => {
+                if !(url.starts_with("http://") || url.starts_with("https://")) {
+                    return Err("Webhook URL must start with http:// or https://".into());
+                }
+            }
+            Output::Osc { host, port, address } => {
+                if host.trim().is_empty() || *port == 0 {
+                    return Err("OSC needs a host and a port".into());
+                }
+                if address.as_ref().is_some_and(|a| !a.starts_with('/')) {
+                    return Err("OSC address must start with /".into());
+                }
+            }
+            Output::Command { program, .. } => {
+                if program.trim().is_empty() {
+                    return Err("Choose a program to run".into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn fire(&self, event: &CueEvent) -> Result<(), String> {
+        match self {
+            Output::Webhook { url } => webhook(url, event),
+            Output::Osc { host, port, address } => {
+                let address = address.clone().unwrap_or_else(|| osc_address(&event.name));
+                osc(host, *port, &address, event.argument.as_deref())
+            }
+            Output::Command { program, args } => command(program, args, event),
+        }
+    }
+}
+
+fn webhook(url: &str, event: &CueEvent) -> Result<(), String> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .http_status_as_error(false)
+        .timeout_global(Some(TIMEOUT))
+        .build()
+        .new_agent();
+    let body = serde_json::to_vec(event).map_err(|e| e.to_string())?;
+    let response = agent
+        .post(url)
+        .header("Content-Type", "application/json")
+        .send(&body[..])
+        .map_err(|e| e.to_string())?;
+    let status = response.status();
+    if status.is_success() {
+        Ok(())
+    } else {
+        Err(format!("Webhook failed: HTTP {}", status.as_u16()))
+    }
+}
+
+/// The default address, with characters OSC reserves for patterns replaced.
+fn osc_address(name: &str) -> String {
+    let name: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if "#*,/?[]{} ".contains(c) { '_' } else { c })
+        .collect();
+    format!("/whispr/cue/{}", name)
+}
+
+fn osc(host: &str, port: u16, address: &str, argument: Option<&str>) -> Result<(), String> {
+    let target = (host.trim(), port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Couldn't resolve {}: {}", host, e))?
+        .next()
+        .ok_or_else(|| format!("Couldn't resolve {}", host))?;
+    let local = if target.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+    let socket = UdpSocket::bind(local).map_err(|e| e.to_string())?;
+    socket
+        .send_to(&osc_message(address, argument), target)
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// An OSC 1.0 message. The argument goes as an int or a float when it reads
+/// as one, so `[@camera 2]` sends the number 2.
+fn osc_message(address: &str, argument: Option<&str>) -> Vec<u8> {
+    let mut packet = Vec::new();
+    osc_string(&mut packet, address);
+    match argument {
+        None => osc_string(&mut packet, ","),
+        Some(argument) => {
+            if let Ok(int) = argument.parse::<i32>() {
+                osc_string(&mut packet, ",i");
+                packet.extend(int.to_be_bytes());
+            } else if let Some(float) = argument.parse::<f32>().ok().filter(|f| f.is_finite()) {
+                osc_string(&mut packet, ",f");
+                packet.extend(float.to_be_bytes());
+            } else {
+                osc_string(&mut packet, ",s");
+                osc_string(&mut packet, argument);
+            }
+        }
+    }
+    packet
+}
+
+/// Null-terminated and padded to a multiple of four bytes.
+fn osc_string(packet: &mut Vec<u8>, text: &str) {
+    packet.extend(text.as_bytes());
+    packet.push(0);
+    while !packet.len().is_multiple_of(4) {
+        packet.push(0);
+    }
+}
+
+/// A program that exits with an error within `TIMEOUT` fails the output;
+/// one still running by then is left to run on its own.
+fn command(program: &str, args: &[String], event: &CueEvent) -> Result<(), String> {
+    let line = event.line.to_string();
+    let args = args.iter().map(|arg| {
+        arg.replace("{cue}", &event.name)
+            .replace("{argument}", event.argument.as_deref().unwrap_or(""))
+            .replace("{line}", &line)
+            .replace("{script_id}", &event.script_id)
+    });
+
+    let mut command = Command::new(program.trim());
+    command
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Couldn't run {}: {}", program, e))?;
+    let started = Instant::now();
+    while started.elapsed() < TIMEOUT {
+        match child.try_wait().map_err(|e| e.to_string())? {
+            Some(status) if status.success() => return Ok(()),
+            Some(status) => return Err(format!("{} failed ({})", program, status)),
+            None => std::thread::sleep(Duration::from_millis(20)),
+        }
+    }
+    // Waited on elsewhere so it's reaped when it does exit
+    std::thread::spawn(move || child.wait().ok());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    fn event(name: &str, argument: Option<&str>) -> CueEvent {
+        CueEvent {
+            script_id: "script-1".into(),
+            index: 0,
+            name: name.into(),
+            argument: argument.map(String::from),
+            line: 4,
+            fired_at: "2026-01-01T00:00:00Z".into(),
+        }
+    }
+
+    /// A local HTTP server that answers one request with `status` and hands
+    /// back the request line and body.
+    fn serve_once(status: &str) -> (String, JoinHandle<(String, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/cue", listener.local_addr().unwrap());
+        let status = status.to_string();
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            let response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+            (request_line, String::from_utf8(body).unwrap())
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn webhook_posts_the_event() {
+        let (url, server) = serve_once("204 No Content");
+        Output::Webhook { url }.fire(&event("camera", Some("2"))).unwrap();
+        let (request_line, body) = server.join().unwrap();
+        assert!(request_line.starts_with("POST /cue "));
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["name"], "camera");
+        assert_eq!(body["argument"], "2");
+        assert_eq!(body["script_id"], "script-1");
+
+        let (url, server) = serve_once("500 Internal Server Error");
+        let error = Output::Webhook { url }.fire(&event("camera", None)).unwrap_err();
+        assert!(error.contains("500"));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn osc_message_reaches_a_udp_listener() {
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        listener.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let output = Output::Osc {
+            host: "127.0.0.1".into(),
+            port: listener.local_addr().unwrap().port(),
+            address: None,
+        };
+        output.fire(&event("Camera", Some("2"))).unwrap();
+
+        let mut buf = [0; 256];
+        let (len, _) = listener.recv_from(&mut buf).unwrap();
+        let mut expected = b"/whispr/cue/camera\0\0,i\0\0".to_vec();
+        expected.extend(2i32.to_be_bytes());
+        assert_eq!(&buf[..len], &expected[..]);
+    }
+
+    #[test]
+    fn osc_arguments_are_typed() {
+        let mut float = b"/x\0\0,f\0\0".to_vec();
+        float.extend(1.5f32.to_be_bytes());
+        assert_eq!(osc_message("/x", Some("1.5")), float);
+        assert_eq!(osc_message("/x", Some("slide")), b"/x\0\0,s\0\0slide\0\0\0");
+        assert_eq!(osc_message("/x", None), b"/x\0\0,\0\0\0");
+        assert_eq!(osc_address("lower third"), "/whispr/cue/lower_third");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn command_runs_with_the_cue_filled_in() {
+        let path = std::env::temp_dir().join(format!("whispr-cue-{}", uuid::Uuid::new_v4()));
+        let output = Output::Command {
+            program: "sh".into(),
+            args: vec![
+                "-c".into(),
+                "printf '%s %s %s' \"$0\" \"$1\" \"$2\" > \"$3\"".into(),
+                "{cue}".into(),
+                "{argument}".into(),
+                "{line}".into(),
+                path.to_string_lossy().into_owned(),
+            ],
+        };
+        output.fire(&event("camera", Some("2"))).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "camera 2 4");
+        std::fs::remove_file(&path).ok();
+
+        let failing = Output::Command {
+            program: "sh".into(),
+            args: vec!["-c".into(), "exit 3".into()],
+        };
+        assert!(failing.fire(&event("camera", None)).is_err());
+    }
+}
@@ -1,9 +1,17 @@
-use rusqlite::{Connection, Result as SqlResult};
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::vault::Vault;
+use rusqlite::{Connection, OpenFlags, Result as SqlResult, Transaction};
 use std::path::PathBuf;
 use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// How often startup refreshes the backup snapshot.
+const BACKUP_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
 
 pub struct Database {
     pub conn: Mutex<Connection>,
+    pub read_only: bool,
+    pub vault: Vault,
 }
 
 impl Database {
@@ -16,29 +24,139 @@ impl Database {
         }
 
         let conn = Connection::open(&db_path)?;
-        let db = Self {
+        conn.busy_timeout(Duration::from_secs(2))?;
+        let mut db = Self {
             conn: Mutex::new(conn),
+            read_only: false,
+            vault: Vault::disabled(),
         };
         db.run_migrations()?;
+        db.check_integrity()?;
+        db.vault = Vault::load(&db.conn.lock().unwrap())?;
+        if Self::backup_is_stale() {
+            db.write_backup();
+        }
+        Ok(db)
+    }
+
+    /// Open the existing database without write access and without running
+    /// migrations, so a damaged or locked file can still be browsed.
+    pub fn open_read_only() -> SqlResult<Self> {
+        let conn = Connection::open_with_flags(
+            Self::db_path(),
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        conn.busy_timeout(Duration::from_secs(2))?;
+        // A database from before encryption existed has no vault table
+        let vault = Vault::load(&conn).unwrap_or_else(|_| Vault::disabled());
+        let db = Self {
+            conn: Mutex::new(conn),
+            read_only: true,
+            vault,
+        };
+        db.check_integrity()?;
         Ok(db)
     }
 
-    fn db_path() -> PathBuf {
-        let base = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
-        base.join("Whispr").join("whispr.db")
+    /// Reject writes up front when the library was opened from the recovery
+    /// window in read-only mode.
+    pub fn ensure_writable(&self) -> AppResult<()> {
+        if self.read_only {
+            return Err(AppError::new(ErrorCode::ReadOnly, "The library is open read-only"));
+        }
+        Ok(())
+    }
+
+    /// Run `f` in one transaction, committed only if it succeeds. Settings
+    /// and script changes made through the same `tx` land together or not at all.
+    pub fn transaction<T>(&self, f: impl FnOnce(&Transaction) -> AppResult<T>) -> AppResult<T> {
+        self.ensure_writable()?;
+        let mut conn = self.conn.lock()?;
+        let tx = conn.transaction()?;
+        let value = f(&tx)?;
+        tx.commit()?;
+        Ok(value)
+    }
+
+    pub fn db_path() -> PathBuf {
+        crate::paths::data_file("whispr.db")
+    }
+
+    /// Snapshot of the last database that opened cleanly.
+    pub fn backup_path() -> PathBuf {
+        Self::db_path().with_extension("db.bak")
     }
 
     fn run_migrations(&self) -> SqlResult<()> {
         let conn = self.conn.lock().unwrap();
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS scripts (
-                id TEXT PRIMARY KEY,
-                title TEXT NOT NULL,
-                content TEXT NOT NULL DEFAULT '',
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
-            );"
-        )?;
+        apply_migrations(&conn)
+    }
+
+    /// Touch the schema and scripts table so a corrupt file fails here
+    /// instead of on the first command.
+    fn check_integrity(&self) -> SqlResult<()> {
+        let conn = self.conn.lock().unwrap();
+        let result: String = conn.query_row("PRAGMA quick_check", [], |row| row.get(0))?;
+        if result != "ok" {
+            return Err(rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CORRUPT),
+                Some(result),
+            ));
+        }
+        conn.query_row("SELECT COUNT(*) FROM scripts", [], |row| row.get::<_, i64>(0))?;
         Ok(())
     }
+
+    /// No backup yet, or none from the last day. Refreshing it on every
+    /// launch would copy the whole library each time.
+    fn backup_is_stale() -> bool {
+        std::fs::metadata(Self::backup_path())
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_none_or(|age| age >= BACKUP_INTERVAL)
+    }
+
+    /// Refresh the backup snapshot. Failures are ignored — a missing backup
+    /// only limits what the recovery window can offer.
+    pub fn write_backup(&self) {
+        let backup = Self::backup_path();
+        let tmp = backup.with_extension("bak.tmp");
+        std::fs::remove_file(&tmp).ok();
+
+        let conn = self.conn.lock().unwrap();
+        if conn
+            .execute("VACUUM INTO ?1", rusqlite::params![tmp.to_string_lossy()])
+            .is_ok()
+        {
+            std::fs::rename(&tmp, &backup).ok();
+        }
+    }
+}
+
+pub fn apply_migrations(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(include_str!("../migrations/001_create_scripts.sql"))?;
+    conn.execute_batch(include_str!("../migrations/002_create_folder_sync_state.sql"))?;
+    conn.execute_batch(include_str!("../migrations/003_create_tombstones.sql"))?;
+    conn.execute_batch(include_str!("../migrations/004_create_vault.sql"))?;
+    conn.execute_batch(include_str!("../migrations/005_create_settings.sql"))?;
+    conn.execute_batch(include_str!("../migrations/006_create_script_variables.sql"))?;
+    // ALTER TABLE can't be repeated, so this one runs only once
+    if !has_column(conn, "scripts", "word_count")? {
+        conn.execute_batch(include_str!("../migrations/007_add_script_stats.sql"))?;
+    }
+    conn.execute_batch(include_str!("../migrations/008_create_script_targets.sql"))?;
+    conn.execute_batch(include_str!("../migrations/009_create_sessions.sql"))?;
+    conn.execute_batch(include_str!("../migrations/010_create_run_lists.sql"))?;
+    conn.execute_batch(include_str!("../migrations/011_create_profiles.sql"))?;
+    Ok(())
+}
+
+pub fn has_column(conn: &Connection, table: &str, column: &str) -> SqlResult<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+        rusqlite::params![table, column],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
 }
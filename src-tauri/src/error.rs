@@ -0,0 +1,163 @@
+//! Error type returned by every Tauri command.
+//!
+//! Serialized as `{ code, message, details? }`. `code` is stable and meant for
+//! the frontend to branch on; `message` is for display. For `invalid_setting`,
+//! `details` is a list of `{ key, message }`, one per rejected field; for
+//! `unresolved_placeholders`, the names still to be filled in.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    ScriptNotFound,
+    NotFound,
+    DatabaseBusy,
+    Database,
+    ReadOnly,
+    LibraryLocked,
+    WrongPassphrase,
+    Encryption,
+    InvalidSetting,
+    InvalidData,
+    Io,
+    Window,
+    Sync,
+    CueOutput,
+    UnresolvedPlaceholders,
+    Internal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppError {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+}
+
+/// One rejected setting, as listed in `details` of an `invalid_setting` error.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub key: String,
+    pub message: String,
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: impl Into<Value>) -> Self {
+        self.details = Some(details.into());
+        self
+    }
+
+    pub fn script_not_found(id: &str) -> Self {
+        Self::new(ErrorCode::ScriptNotFound, "This script no longer exists").with_details(id)
+    }
+
+    /// For queries by script id: a missing row means the script was deleted.
+    pub fn script_lookup(id: &str) -> impl FnOnce(rusqlite::Error) -> Self + '_ {
+        move |e| match e {
+            rusqlite::Error::QueryReturnedNoRows => Self::script_not_found(id),
+            e => e.into(),
+        }
+    }
+
+    pub fn invalid_setting(key: &str, message: impl Into<String>) -> Self {
+        Self::invalid_settings(vec![FieldError {
+            key: key.to_string(),
+            message: message.into(),
+        }])
+    }
+
+    pub fn invalid_settings(fields: Vec<FieldError>) -> Self {
+        let message = match fields.as_slice() {
+            [only] => only.message.clone(),
+            _ => format!("{} settings are invalid", fields.len()),
+        };
+        Self::new(ErrorCode::InvalidSetting, message)
+            .with_details(serde_json::to_value(fields).unwrap_or_default())
+    }
+
+    pub fn unresolved_placeholders(names: Vec<String>) -> Self {
+        let message = match names.as_slice() {
+            [only] => format!("Fill in [{}] before prompting", only),
+            _ => format!("{} placeholders need filling in before prompting", names.len()),
+        };
+        Self::new(ErrorCode::UnresolvedPlaceholders, message).with_details(names)
+    }
+
+    pub fn sync(message: String) -> Self {
+        Self::new(ErrorCode::Sync, message)
+    }
+
+    pub fn cue_output(message: String) -> Self {
+        Self::new(ErrorCode::CueOutput, message)
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        match &e {
+            rusqlite::Error::QueryReturnedNoRows => Self::new(ErrorCode::NotFound, "Not found"),
+            rusqlite::Error::SqliteFailure(code, _)
+                if matches!(
+                    code.code,
+                    rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked
+                ) =>
+            {
+                Self::new(ErrorCode::DatabaseBusy, "The database is in use by another program")
+                    .with_details(e.to_string())
+            }
+            _ => Self::new(ErrorCode::Database, "Database error").with_details(e.to_string()),
+        }
+    }
+}
+
+impl<T> From<std::sync::PoisonError<T>> for AppError {
+    fn from(e: std::sync::PoisonError<T>) -> Self {
+        Self::new(ErrorCode::Internal, "Database connection is unavailable").with_details(e.to_string())
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        Self::new(ErrorCode::Io, e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::new(ErrorCode::InvalidData, e.to_string())
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(e: tauri::Error) -> Self {
+        Self::new(ErrorCode::Window, e.to_string())
+    }
+}
+
+impl From<AppError> for String {
+    fn from(e: AppError) -> Self {
+        e.message
+    }
+}
@@ -0,0 +1,44 @@
+//! Crash-safe file replacement for the JSON files in the data directory.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Replace `path` with `data` so that a crash at any point leaves either the
+/// old file or the new one, never a truncated mix. The previous version is
+/// kept as `<name>.bak`.
+pub fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    fs::create_dir_all(dir)?;
+
+    let tmp = sibling(path, "tmp");
+    {
+        let mut file = File::create(&tmp)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+    }
+
+    if path.exists() {
+        // Copy rather than rename so `path` itself never goes missing
+        fs::copy(path, backup_path(path))?;
+    }
+    fs::rename(&tmp, path)?;
+
+    // Make the rename itself durable
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+
+    Ok(())
+}
+
+/// Where `write_atomic` keeps the previous version of `path`.
+pub fn backup_path(path: &Path) -> PathBuf {
+    sibling(path, "bak")
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
@@ -1,10 +1,29 @@
+mod analysis;
 mod commands;
+mod cues;
 mod db;
+mod error;
+mod files;
+mod markup;
 mod models;
+mod paths;
+mod profiles;
+mod recovery;
+mod run_lists;
+mod sessions;
+mod settings;
+mod settings_export;
+mod shortcuts;
 mod state;
+mod sync;
+mod text;
 mod tray;
+mod variables;
+mod vault;
 
+use db::Database;
 use state::AppState;
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -15,10 +34,21 @@ pub fn run() {
             tauri_plugin_global_shortcut::Builder::new()
                 .build(),
         )
-        .manage(AppState::new())
         .setup(|app| {
+            match Database::new() {
+                Ok(db) => {
+                    let locked = db.vault.is_locked();
+                    app.manage(AppState::new(db));
+                    sync::resume(app.handle());
+                    if locked {
+                        vault::prompt_unlock(app.handle())?;
+                    }
+                }
+                Err(e) => recovery::enter(app.handle(), e)?,
+            }
             tray::setup_tray(app.handle())?;
-            setup_global_shortcuts(app.handle())?;
+            shortcuts::setup_global_shortcuts(app.handle())?;
+            vault::watch_idle(app.handle());
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -27,42 +57,68 @@ pub fn run() {
             commands::scripts::update_script,
             commands::scripts::delete_script,
             commands::scripts::get_recent_scripts,
+            commands::scripts::parse_script,
+            commands::scripts::analyze_script,
+            commands::scripts::set_target_duration,
+            commands::scripts::compute_target_speed,
+            commands::scripts::get_script_variables,
+            commands::scripts::set_script_variables,
             commands::settings::get_settings,
             commands::settings::save_settings,
             commands::settings::update_setting,
+            commands::settings::update_settings,
+            commands::settings::get_settings_schema,
+            commands::settings::get_settings_history,
+            commands::settings::export_settings,
+            commands::settings::preview_settings_import,
+            commands::settings::import_settings,
+            commands::profiles::list_profiles,
+            commands::profiles::create_profile,
+            commands::profiles::duplicate_profile,
+            commands::profiles::rename_profile,
+            commands::profiles::delete_profile,
+            commands::profiles::activate_profile,
             commands::window::open_topbar_prompter,
             commands::window::open_floating_prompter,
             commands::window::close_prompter,
+            commands::window::report_prompter_status,
+            commands::cues::report_cue,
+            commands::cues::get_cue_outputs,
+            commands::cues::save_cue_output,
+            commands::cues::delete_cue_output,
+            commands::cues::test_cue_output,
+            commands::sessions::list_sessions,
+            commands::sessions::get_session_stats,
+            commands::run_lists::list_run_lists,
+            commands::run_lists::save_run_list,
+            commands::run_lists::delete_run_list,
+            commands::run_lists::get_run_list_status,
+            commands::run_lists::start_run_list,
+            commands::run_lists::run_list_next,
+            commands::run_lists::run_list_previous,
             commands::window::open_settings_window,
             commands::system::prevent_sleep,
             commands::system::read_file_content,
+            commands::system::get_data_location,
+            commands::sync::get_folder_sync,
+            commands::sync::set_folder_sync,
+            commands::sync::sync_folder_now,
+            commands::sync::get_webdav_sync,
+            commands::sync::set_webdav_sync,
+            commands::sync::sync_webdav_now,
+            commands::recovery::get_startup_error,
+            commands::recovery::recovery_retry,
+            commands::recovery::recovery_restore_backup,
+            commands::recovery::recovery_start_fresh,
+            commands::recovery::recovery_open_read_only,
+            commands::vault::get_vault_status,
+            commands::vault::unlock_library,
+            commands::vault::lock_library,
+            commands::vault::enable_encryption,
+            commands::vault::disable_encryption,
+            commands::vault::change_passphrase,
+            commands::vault::set_auto_lock,
         ])
         .run(tauri::generate_context!())
         .expect("error while running Whispr");
 }
-
-fn setup_global_shortcuts(app: &tauri::AppHandle) -> Result<(), Box<dyn std::error::Error>> {
-    use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
-    use tauri::Emitter;
-
-    // Ctrl+Shift+W — toggle prompter
-    let toggle_shortcut: Shortcut = "CmdOrCtrl+Shift+W".parse()?;
-    // Ctrl+Shift+P — pause/resume
-    let pause_shortcut: Shortcut = "CmdOrCtrl+Shift+P".parse()?;
-
-    let app_handle = app.clone();
-    app.global_shortcut().on_shortcuts(
-        [toggle_shortcut, pause_shortcut],
-        move |_app, shortcut, event| {
-            if event.state == ShortcutState::Pressed {
-                if shortcut == &toggle_shortcut {
-                    app_handle.emit("global-toggle-prompter", ()).ok();
-                } else if shortcut == &pause_shortcut {
-                    app_handle.emit("global-pause-prompter", ()).ok();
-                }
-            }
-        },
-    )?;
-
-    Ok(())
-}
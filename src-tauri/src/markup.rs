@@ -0,0 +1,602 @@
+//! Script markup, parsed into the document the prompters display.
+//!
+//! The syntax is the Markdown subset the templates already use, plus cues and
+//! comments:
+//!
+//! - `# Title` and `## Title` start a section
+//! - blank lines separate paragraphs
+//! - `**bold**` and `*italic*`
+//! - `[PAUSE]`, `[SLIDE 5]` and the other words in `CUES` are cues: shown
+//!   to the presenter, never read
+//! - `[@name argument]` is a cue with any name, like `[@camera 2]`, for
+//!   markers meant to trigger something (see cues.rs)
+//! - any other `[Text]` is a placeholder to fill in, like `[Name]`
+//! - lines starting with `//`, and anything inside `<!-- -->`, are comments
+//!   hidden from the prompter
+//! - `\` makes the next character literal
+//!
+//! Problems don't stop parsing; each becomes a diagnostic with the line and
+//! column it was found at.
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Bracketed words that are cues rather than placeholders.
+const CUES: &[&str] = &["PAUSE", "BEAT", "BREATH", "BREATHE", "SLIDE", "NEXT", "LOOK", "SMILE"];
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Document {
+    pub sections: Vec<Section>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Text under one heading. The first section has no title when the script
+/// doesn't start with a heading.
+#[derive(Debug, Clone, Serialize)]
+pub struct Section {
+    pub title: Option<String>,
+    /// The title as parsed, so placeholders in it can be found and filled
+    #[serde(skip)]
+    pub heading: Vec<Inline>,
+    pub level: u8,
+    pub line: usize,
+    pub paragraphs: Vec<Paragraph>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Paragraph {
+    pub line: usize,
+    pub inlines: Vec<Inline>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Inline {
+    Text { text: String, strong: bool, em: bool },
+    Cue { name: String, argument: Option<String>, line: usize },
+    Placeholder { name: String, line: usize, strong: bool, em: bool },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// `line` and `column` are 1-based; columns count characters.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Document {
+    pub fn inlines(&self) -> impl Iterator<Item = &Inline> {
+        self.sections
+            .iter()
+            .flat_map(|s| &s.paragraphs)
+            .flat_map(|p| &p.inlines)
+    }
+
+    /// Every cue in reading order. The prompter refers to cues by their
+    /// position in this list.
+    pub fn cues(&self) -> impl Iterator<Item = &Inline> {
+        self.inlines().filter(|inline| matches!(inline, Inline::Cue { .. }))
+    }
+
+    /// Placeholder names in the order they first appear, titles included.
+    pub fn placeholders(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        let inlines = self.sections.iter().flat_map(|s| {
+            s.heading.iter().chain(s.paragraphs.iter().flat_map(|p| &p.inlines))
+        });
+        for inline in inlines {
+            if let Inline::Placeholder { name, .. } = inline {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+        names
+    }
+
+    /// Replace placeholders that have a value with text in their emphasis,
+    /// merged into the words around them so `$[Amount]` reads as one word.
+    /// Placeholders without a value are left for the prompter to highlight.
+    pub fn fill(&mut self, values: &HashMap<String, String>) {
+        for section in &mut self.sections {
+            if section.title.is_some() {
+                section.heading = fill_inlines(std::mem::take(&mut section.heading), values);
+                section.title = Some(plain_text(&section.heading));
+            }
+            for paragraph in &mut section.paragraphs {
+                paragraph.inlines = fill_inlines(std::mem::take(&mut paragraph.inlines), values);
+            }
+        }
+    }
+}
+
+fn fill_inlines(inlines: Vec<Inline>, values: &HashMap<String, String>) -> Vec<Inline> {
+    let mut out = InlineBuilder(Vec::new());
+    for inline in inlines {
+        match inline {
+            Inline::Placeholder { ref name, strong, em, .. } => match values.get(name) {
+                Some(value) => out.text(value, strong, em),
+                None => out.push(inline),
+            },
+            Inline::Text { text, strong, em } => out.text(&text, strong, em),
+            inline => out.push(inline),
+        }
+    }
+    out.finish()
+}
+
+pub fn parse(content: &str) -> Document {
+    let mut diagnostics = Vec::new();
+    let lines = strip_comments(content, &mut diagnostics);
+
+    let mut sections: Vec<Section> = Vec::new();
+    let mut pending: Vec<(usize, &str)> = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let number = index + 1;
+        let Some(line) = line else {
+            // Comment lines don't end a paragraph
+            continue;
+        };
+        if line.trim().is_empty() {
+            end_paragraph(&mut pending, &mut sections, &mut diagnostics);
+        } else if let Some((level, column, title)) = heading(line) {
+            end_paragraph(&mut pending, &mut sections, &mut diagnostics);
+            let heading = parse_heading(number, column, title, &mut diagnostics);
+            sections.push(Section {
+                title: Some(plain_text(&heading)),
+                heading,
+                level,
+                line: number,
+                paragraphs: Vec::new(),
+            });
+        } else {
+            pending.push((number, line.as_str()));
+        }
+    }
+    end_paragraph(&mut pending, &mut sections, &mut diagnostics);
+
+    diagnostics.sort_by_key(|d| (d.line, d.column));
+    Document { sections, diagnostics }
+}
+
+fn end_paragraph(
+    pending: &mut Vec<(usize, &str)>,
+    sections: &mut Vec<Section>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if pending.is_empty() {
+        return;
+    }
+    let paragraph = parse_paragraph(pending, diagnostics);
+    pending.clear();
+    if paragraph.inlines.is_empty() {
+        return;
+    }
+    if sections.is_empty() {
+        sections.push(Section {
+            title: None,
+            heading: Vec::new(),
+            level: 0,
+            line: paragraph.line,
+            paragraphs: Vec::new(),
+        });
+    }
+    if let Some(section) = sections.last_mut() {
+        section.paragraphs.push(paragraph);
+    }
+}
+
+/// Each line of `content` with comments removed; `None` for a line that was
+/// only comment. Line numbers are unchanged.
+fn strip_comments(content: &str, diagnostics: &mut Vec<Diagnostic>) -> Vec<Option<String>> {
+    let mut lines = Vec::new();
+    // Line and column where an unclosed `<!--` started
+    let mut open: Option<(usize, usize)> = None;
+
+    for (index, line) in content.lines().enumerate() {
+        let number = index + 1;
+        if open.is_none() && line.trim_start().starts_with("//") {
+            lines.push(None);
+            continue;
+        }
+
+        let mut kept = String::new();
+        let mut rest = line;
+        let mut commented = open.is_some();
+        loop {
+            if open.is_some() {
+                match rest.find("-->") {
+                    Some(end) => {
+                        rest = &rest[end + 3..];
+                        open = None;
+                    }
+                    None => break,
+                }
+            } else {
+                match rest.find("<!--") {
+                    Some(start) => {
+                        kept.push_str(&rest[..start]);
+                        let column = line[..line.len() - rest.len() + start].chars().count() + 1;
+                        open = Some((number, column));
+                        commented = true;
+                        rest = &rest[start + 4..];
+                    }
+                    None => {
+                        kept.push_str(rest);
+                        break;
+                    }
+                }
+            }
+        }
+        // A line that held nothing but comment is dropped like a `//` line
+        lines.push(Some(kept).filter(|kept| !(commented && kept.trim().is_empty())));
+    }
+
+    if let Some((line, column)) = open {
+        diagnostics.push(Diagnostic {
+            line,
+            column,
+            severity: Severity::Error,
+            message: "Comment is never closed with -->; the rest of the script is hidden".into(),
+        });
+    }
+    lines
+}
+
+/// Level, starting column (0-based, in characters) and text of a heading.
+fn heading(line: &str) -> Option<(u8, usize, &str)> {
+    let trimmed = line.trim_start();
+    let level = trimmed.chars().take_while(|&c| c == '#').count();
+    let rest = &trimmed[level..];
+    if level == 0 || !(rest.is_empty() || rest.starts_with(char::is_whitespace)) {
+        return None;
+    }
+    let title = rest.trim_start();
+    let column = line[..line.len() - title.len()].chars().count();
+    Some((level.min(6) as u8, column, title.trim_end()))
+}
+
+/// Inline markup of a heading's `title`, which starts `column` characters
+/// into its line, with problems reported where they are in the line.
+fn parse_heading(
+    number: usize,
+    column: usize,
+    title: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<Inline> {
+    let first = diagnostics.len();
+    let inlines = parse_inlines(&[(number, title)], diagnostics);
+    for diagnostic in &mut diagnostics[first..] {
+        diagnostic.column += column;
+    }
+    inlines
+}
+
+/// Heading text without emphasis markers.
+fn plain_text(inlines: &[Inline]) -> String {
+    inlines
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text { text, .. } => text.clone(),
+            Inline::Cue { name, .. } => format!("[{}]", name),
+            Inline::Placeholder { name, .. } => format!("[{}]", name),
+        })
+        .collect()
+}
+
+fn parse_paragraph(lines: &[(usize, &str)], diagnostics: &mut Vec<Diagnostic>) -> Paragraph {
+    Paragraph {
+        line: lines.first().map(|(line, _)| *line).unwrap_or(1),
+        inlines: parse_inlines(lines, diagnostics),
+    }
+}
+
+/// Inline markup over the lines of one paragraph. Emphasis may span lines;
+/// brackets may not.
+fn parse_inlines(lines: &[(usize, &str)], diagnostics: &mut Vec<Diagnostic>) -> Vec<Inline> {
+    let mut out = InlineBuilder(Vec::new());
+    let mut strong: Option<(usize, usize)> = None;
+    let mut em: Option<(usize, usize)> = None;
+
+    for (line_index, &(number, line)) in lines.iter().enumerate() {
+        if line_index > 0 {
+            out.text(" ", strong.is_some(), em.is_some());
+        }
+        let chars: Vec<char> = line.trim().chars().collect();
+        let indent = line.chars().take_while(|c| c.is_whitespace()).count();
+        let mut i = 0;
+        while i < chars.len() {
+            let column = indent + i + 1;
+            match chars[i] {
+                '\\' if i + 1 < chars.len() => {
+                    out.text(&chars[i + 1].to_string(), strong.is_some(), em.is_some());
+                    i += 2;
+                }
+                '*' if chars.get(i + 1) == Some(&'*') => {
+                    strong = match strong {
+                        Some(_) => None,
+                        None => Some((number, column)),
+                    };
+                    i += 2;
+                }
+                '*' if em.is_some() || chars.get(i + 1).is_some_and(|c| !c.is_whitespace()) => {
+                    em = match em {
+                        Some(_) => None,
+                        None => Some((number, column)),
+                    };
+                    i += 1;
+                }
+                '[' => match chars[i + 1..].iter().position(|&c| c == ']') {
+                    Some(length) => {
+                        let inner: String = chars[i + 1..i + 1 + length].iter().collect();
+                        match bracket(inner.trim(), number, strong.is_some(), em.is_some()) {
+                            Ok(inline) => out.push(inline),
+                            Err(message) => diagnostics.push(Diagnostic {
+                                line: number,
+                                column,
+                                severity: Severity::Warning,
+                                message: message.into(),
+                            }),
+                        }
+                        i += length + 2;
+                    }
+                    None => {
+                        diagnostics.push(Diagnostic {
+                            line: number,
+                            column,
+                            severity: Severity::Warning,
+                            message: "[ is never closed with ] on this line".into(),
+                        });
+                        out.text("[", strong.is_some(), em.is_some());
+                        i += 1;
+                    }
+                },
+                c => {
+                    out.text(&c.to_string(), strong.is_some(), em.is_some());
+                    i += 1;
+                }
+            }
+        }
+    }
+
+    for (open, marker, name) in [(strong, "**", "Bold"), (em, "*", "Italic")] {
+        if let Some((line, column)) = open {
+            diagnostics.push(Diagnostic {
+                line,
+                column,
+                severity: Severity::Warning,
+                message: format!("{} text is never closed with {}", name, marker),
+            });
+        }
+    }
+    out.finish()
+}
+
+fn bracket(inner: &str, line: usize, strong: bool, em: bool) -> Result<Inline, &'static str> {
+    if inner.is_empty() {
+        return Err("Empty brackets");
+    }
+    let marker = inner.strip_prefix('@').map(str::trim_start);
+    let (word, argument) = match marker.unwrap_or(inner).split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, Some(rest.trim().to_string())),
+        None => (marker.unwrap_or(inner), None),
+    };
+    if marker.is_some() && word.is_empty() {
+        return Err("Cue marker has no name after @");
+    }
+    if marker.is_some() || CUES.contains(&word) {
+        return Ok(Inline::Cue {
+            name: word.to_string(),
+            argument: argument.filter(|a| !a.is_empty()),
+            line,
+        });
+    }
+    Ok(Inline::Placeholder {
+        name: inner.to_string(),
+        line,
+        strong,
+        em,
+    })
+}
+
+/// Collects inlines, merging neighbouring text with the same emphasis.
+struct InlineBuilder(Vec<Inline>);
+
+impl InlineBuilder {
+    fn text(&mut self, text: &str, strong: bool, em: bool) {
+        if let Some(Inline::Text { text: last, strong: s, em: e }) = self.0.last_mut() {
+            if *s == strong && *e == em {
+                last.push_str(text);
+                return;
+            }
+        }
+        self.0.push(Inline::Text {
+            text: text.to_string(),
+            strong,
+            em,
+        });
+    }
+
+    fn push(&mut self, inline: Inline) {
+        self.0.push(inline);
+    }
+
+    /// Drops whitespace-only text at the ends and next to cues. Between two
+    /// placeholders it stays, so filled values don't run together.
+    fn finish(self) -> Vec<Inline> {
+        let inlines = self.0;
+        let keep = |i: usize| match &inlines[i] {
+            Inline::Text { text, .. } if text.trim().is_empty() => {
+                let edge_or_cue = |j: Option<usize>| {
+                    j.and_then(|j| inlines.get(j))
+                        .is_none_or(|inline| matches!(inline, Inline::Cue { .. }))
+                };
+                !edge_or_cue(i.checked_sub(1)) && !edge_or_cue(Some(i + 1))
+            }
+            _ => true,
+        };
+        let kept: Vec<bool> = (0..inlines.len()).map(keep).collect();
+        inlines
+            .into_iter()
+            .zip(kept)
+            .filter_map(|(inline, keep)| keep.then_some(inline))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str, strong: bool, em: bool) -> Inline {
+        Inline::Text {
+            text: text.into(),
+            strong,
+            em,
+        }
+    }
+
+    fn placeholder(name: &str, strong: bool, em: bool) -> Inline {
+        Inline::Placeholder {
+            name: name.into(),
+            line: 1,
+            strong,
+            em,
+        }
+    }
+
+    #[test]
+    fn sections_and_paragraphs() {
+        let doc = parse("Intro line\n\n# Opening\n\nHello\nthere\n\n## Detail\nMore");
+        let titles: Vec<_> = doc.sections.iter().map(|s| s.title.clone()).collect();
+        assert_eq!(titles, [None, Some("Opening".into()), Some("Detail".into())]);
+        assert_eq!(doc.sections[1].line, 3);
+        assert_eq!(doc.sections[1].paragraphs[0].line, 5);
+        assert_eq!(doc.sections[1].paragraphs[0].inlines, [text("Hello there", false, false)]);
+        assert_eq!(doc.sections[2].level, 2);
+        assert!(doc.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn emphasis_cues_and_placeholders() {
+        let doc = parse("My name is **[Name]** and I *love* this. [PAUSE] [SLIDE 5] $[Amount]");
+        let inlines = &doc.sections[0].paragraphs[0].inlines;
+        assert_eq!(inlines[0], text("My name is ", false, false));
+        assert_eq!(inlines[1], placeholder("Name", true, false));
+        assert_eq!(inlines[3], text("love", false, true));
+        assert_eq!(inlines[5], Inline::Cue { name: "PAUSE".into(), argument: None, line: 1 });
+        assert_eq!(
+            inlines[6],
+            Inline::Cue { name: "SLIDE".into(), argument: Some("5".into()), line: 1 }
+        );
+        assert_eq!(inlines[8], placeholder("Amount", false, false));
+    }
+
+    #[test]
+    fn comments_are_hidden_and_keep_line_numbers() {
+        let doc = parse("// note to self\nFirst <!-- aside -->line\n<!--\nhidden\n-->\nSecond [oops");
+        let paragraph = &doc.sections[0].paragraphs[0];
+        assert_eq!(paragraph.line, 2);
+        assert_eq!(paragraph.inlines[0], text("First line Second [oops", false, false));
+        assert_eq!(doc.diagnostics.len(), 1);
+        assert_eq!((doc.diagnostics[0].line, doc.diagnostics[0].column), (6, 8));
+    }
+
+    #[test]
+    fn unclosed_markup_is_reported_where_it_opened() {
+        let doc = parse("Fine\n\nThis is **bold\nto the end\n\n<!-- never closed");
+        let found: Vec<_> = doc.diagnostics.iter().map(|d| (d.line, d.column, d.severity)).collect();
+        assert_eq!(found, [(3, 9, Severity::Warning), (6, 1, Severity::Error)]);
+    }
+
+    #[test]
+    fn placeholders_are_filled_into_surrounding_words() {
+        let mut doc = parse("Hi [First] [Last], that's $[Amount]. [PAUSE] Bye [First]");
+        assert_eq!(doc.placeholders(), ["First", "Last", "Amount"]);
+
+        let values = HashMap::from([
+            ("First".to_string(), "Ada".to_string()),
+            ("Amount".to_string(), "500".to_string()),
+        ]);
+        doc.fill(&values);
+        let inlines = &doc.sections[0].paragraphs[0].inlines;
+        assert_eq!(inlines[0], text("Hi Ada ", false, false));
+        assert_eq!(inlines[1], placeholder("Last", false, false));
+        assert_eq!(inlines[2], text(", that's $500. ", false, false));
+        assert_eq!(inlines[4], text(" Bye Ada", false, false));
+        assert_eq!(doc.placeholders(), ["Last"]);
+    }
+
+    #[test]
+    fn filled_placeholders_keep_their_emphasis() {
+        let mut doc = parse("Meet **[Name]**, our *[Role]*.");
+        doc.fill(&HashMap::from([
+            ("Name".to_string(), "Ada".to_string()),
+            ("Role".to_string(), "host".to_string()),
+        ]));
+        assert_eq!(
+            doc.sections[0].paragraphs[0].inlines,
+            [
+                text("Meet ", false, false),
+                text("Ada", true, false),
+                text(", our ", false, false),
+                text("host", false, true),
+                text(".", false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn placeholders_in_titles_are_found_and_filled() {
+        let mut doc = parse("# Welcome to **[Company]**\n\nHello [Name]");
+        assert_eq!(doc.sections[0].title.as_deref(), Some("Welcome to [Company]"));
+        assert_eq!(doc.placeholders(), ["Company", "Name"]);
+
+        doc.fill(&HashMap::from([("Company".to_string(), "Acme".to_string())]));
+        assert_eq!(doc.sections[0].title.as_deref(), Some("Welcome to Acme"));
+        assert_eq!(doc.placeholders(), ["Name"]);
+    }
+
+    #[test]
+    fn problems_in_titles_are_reported() {
+        let doc = parse("Intro\n\n## The *big [] reveal");
+        assert_eq!(doc.sections[1].title.as_deref(), Some("The big  reveal"));
+        let found: Vec<_> = doc.diagnostics.iter().map(|d| (d.line, d.column)).collect();
+        assert_eq!(found, [(3, 8), (3, 13)]);
+    }
+
+    #[test]
+    fn cue_markers_take_any_name() {
+        let doc = parse("[@camera 2] Welcome back. [@ lower-third  Ada Lovelace ] [@]\n[PAUSE]");
+        let cues: Vec<_> = doc.cues().cloned().collect();
+        assert_eq!(
+            cues,
+            [
+                Inline::Cue { name: "camera".into(), argument: Some("2".into()), line: 1 },
+                Inline::Cue { name: "lower-third".into(), argument: Some("Ada Lovelace".into()), line: 1 },
+                Inline::Cue { name: "PAUSE".into(), argument: None, line: 2 },
+            ]
+        );
+        assert_eq!(doc.diagnostics.len(), 1);
+        assert_eq!(doc.diagnostics[0].column, 58);
+    }
+
+    #[test]
+    fn literal_stars_and_escapes() {
+        let doc = parse("5 * 3 = 15 and \\*not italic\\*");
+        assert_eq!(
+            doc.sections[0].paragraphs[0].inlines,
+            [text("5 * 3 = 15 and *not italic*", false, false)]
+        );
+        assert!(doc.diagnostics.is_empty());
+    }
+}
@@ -7,64 +7,289 @@ pub struct Script {
     pub content: String,
     pub created_at: String,
     pub updated_at: String,
+    /// Stored with the script so the library can show it without parsing;
+    /// `None` until the script has been counted since its content changed
+    pub word_count: Option<u32>,
+    /// At the configured scroll speed, countdown excluded
+    pub estimated_seconds: Option<f64>,
 }
 
+/// A placeholder in a script with the value stored for it, if any.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptVariable {
+    pub name: String,
+    pub value: Option<String>,
+}
+
+/// One time a script was prompted. See `sessions`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Session {
+    pub id: String,
+    pub script_id: String,
+    pub mode: PrompterMode,
+    pub started_at: String,
+    /// `None` while prompting, or when Whispr quit before the prompter closed
+    pub ended_at: Option<String>,
+    /// Time spent scrolling; the countdown and pauses aren't counted
+    pub duration_seconds: f64,
+    pub pauses: u32,
+    pub speed_changes: u32,
+    /// Furthest point reached, 0.0 to 1.0
+    pub completion: f64,
+    pub word_count: u32,
+    pub actual_wpm: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionStats {
+    /// Sessions that got past the countdown
+    pub sessions: u32,
+    /// Sessions that reached the end
+    pub completed: u32,
+    pub average_duration_seconds: Option<f64>,
+    pub average_wpm: Option<f64>,
+    pub average_completion: Option<f64>,
+    pub average_pauses: Option<f64>,
+    /// Change per run-through over `trend`: negative means getting quicker
+    pub duration_trend: Option<f64>,
+    pub wpm_trend: Option<f64>,
+    /// Recent complete run-throughs, oldest first
+    pub trend: Vec<TrendPoint>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrendPoint {
+    pub started_at: String,
+    pub duration_seconds: f64,
+    pub actual_wpm: Option<f64>,
+    pub pauses: u32,
+}
+
+/// Scripts prompted one after another, like the segments of a show. See
+/// `run_lists`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunList {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    /// What happens when a script finishes
+    pub end_action: RunListEndAction,
+    pub items: Vec<RunListItem>,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunListEndAction {
+    /// Open the next script after the gap, stopping after the last
+    Advance,
+    /// Stay on the finished script until Next
+    Wait,
+    /// Like `Advance`, going back to the first after the last
+    Loop,
+}
+
+/// One script in a run list. Settings left `None` come from the settings
+/// and the script itself, as when it's prompted alone.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunListItem {
+    pub script_id: String,
+    /// Filled in when listed
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub mode: Option<PrompterMode>,
+    #[serde(default)]
+    pub speed: Option<f64>,
+    /// Wait after this script finishes before the next one opens
+    #[serde(default)]
+    pub gap_seconds: f64,
+}
+
+/// Where a run list stands while it's being prompted.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunListStatus {
+    pub run_list_id: String,
+    pub name: String,
+    pub index: usize,
+    pub count: usize,
+    pub script_id: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrompterPhase {
+    #[default]
+    Idle,
+    CountingDown,
+    Scrolling,
+    Paused,
+    Finished,
+}
+
+/// The prompter as the tray shows it. The script is set when a prompter
+/// opens; phase, progress and speed come from the prompter window.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PrompterStatus {
+    pub phase: PrompterPhase,
+    pub script_id: Option<String>,
+    pub title: String,
+    /// 0.0 at the first word, 1.0 at the last
+    pub progress: f64,
+    /// Engine scroll speed (10–200); shown as words per minute, three times this
+    pub speed: f64,
+}
+
+/// Declares a settings enumeration together with its serialized values and
+/// display labels, which the settings schema hands to the UI.
+macro_rules! setting_enum {
+    ($name:ident { $($variant:ident = $value:literal / $label:literal),+ $(,)? }) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+        pub enum $name {
+            $(#[serde(rename = $value)] $variant),+
+        }
+
+        impl $name {
+            pub const OPTIONS: &'static [(&'static str, &'static str)] = &[$(($value, $label)),+];
+        }
+    };
+}
+
+setting_enum!(FontFamily {
+    System = "System" / "System",
+    Serif = "Serif" / "Serif",
+    Monospace = "Monospace" / "Monospace",
+    Rounded = "Rounded" / "Rounded",
+});
+
+setting_enum!(GlowStyle {
+    Rainbow = "rainbow" / "Rainbow",
+    Blue = "blue" / "Blue",
+    Green = "green" / "Green",
+    Off = "off" / "Off",
+});
+
+setting_enum!(FloatingTheme {
+    Dark = "dark" / "Dark",
+    Light = "light" / "Light",
+    HighContrast = "highContrast" / "High Contrast",
+    GreenScreen = "greenScreen" / "Green Screen",
+});
+
+setting_enum!(NotchPosition {
+    Top = "top" / "Top",
+    Bottom = "bottom" / "Bottom",
+});
+
+setting_enum!(PrompterMode {
+    Notch = "notch" / "Top Bar",
+    Floating = "floating" / "Floating",
+});
+
+setting_enum!(AppearanceMode {
+    Dark = "dark" / "Dark",
+    Light = "light" / "Light",
+    System = "system" / "System",
+});
+
+setting_enum!(EndAction {
+    Stop = "stop" / "Stop",
+    Loop = "loop" / "Loop",
+    Close = "close" / "Close Window",
+});
+
+/// Current settings format. Bump it together with a new entry in
+/// `settings::MIGRATIONS` when a field is renamed or changes type.
+pub const SETTINGS_VERSION: u32 = 1;
+
+/// Missing fields take their value from `Default`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings {
+    pub version: u32,
+
     // Top bar specific
     pub notch_font_size: f64,
     pub notch_scroll_speed: f64,
     pub notch_width_percent: f64,
     pub notch_height: f64,
-    pub notch_font_family: String,
+    pub notch_font_family: FontFamily,
     pub notch_opacity: f64,
-    pub notch_glow_style: String,
-    pub notch_position: String,
+    pub notch_glow_style: GlowStyle,
+    pub notch_position: NotchPosition,
     pub notch_line_count: u32,
     pub notch_show_timer: bool,
 
     // Floating specific
     pub floating_font_size: f64,
     pub floating_scroll_speed: f64,
-    pub floating_font_family: String,
-    pub floating_theme: String,
+    pub floating_font_family: FontFamily,
+    pub floating_theme: FloatingTheme,
     pub text_color_hex: String,
     pub mirror_mode: bool,
 
     // General
     pub countdown_seconds: u32,
-    pub prompter_mode: String,
+    pub prompter_mode: PrompterMode,
     pub show_menu_bar_icon: bool,
-    pub appearance_mode: String,
-    pub end_action: String,
+    pub appearance_mode: AppearanceMode,
+    pub end_action: EndAction,
+    /// Scroll only while `hold_shortcut` is held down
+    pub hold_to_scroll: bool,
+    pub hold_shortcut: String,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
+            version: SETTINGS_VERSION,
+
             notch_font_size: 20.0,
             notch_scroll_speed: 50.0,
             notch_width_percent: 55.0,
             notch_height: 100.0,
-            notch_font_family: "System".into(),
+            notch_font_family: FontFamily::System,
             notch_opacity: 0.92,
-            notch_glow_style: "rainbow".into(),
-            notch_position: "top".into(),
+            notch_glow_style: GlowStyle::Rainbow,
+            notch_position: NotchPosition::Top,
             notch_line_count: 2,
             notch_show_timer: true,
 
             floating_font_size: 32.0,
             floating_scroll_speed: 50.0,
-            floating_font_family: "System".into(),
-            floating_theme: "dark".into(),
+            floating_font_family: FontFamily::System,
+            floating_theme: FloatingTheme::Dark,
             text_color_hex: "#FFFFFF".into(),
             mirror_mode: false,
 
             countdown_seconds: 3,
-            prompter_mode: "notch".into(),
+            prompter_mode: PrompterMode::Notch,
             show_menu_bar_icon: true,
-            appearance_mode: "dark".into(),
-            end_action: "stop".into(),
+            appearance_mode: AppearanceMode::Dark,
+            end_action: EndAction::Stop,
+            hold_to_scroll: false,
+            hold_shortcut: "CmdOrCtrl+Shift+Space".into(),
+        }
+    }
+}
+
+impl Settings {
+    /// Scroll speed of the prompter `mode` opens.
+    pub fn scroll_speed(&self, mode: PrompterMode) -> f64 {
+        match mode {
+            PrompterMode::Notch => self.notch_scroll_speed,
+            PrompterMode::Floating => self.floating_scroll_speed,
+        }
+    }
+
+    pub fn font_size(&self, mode: PrompterMode) -> f64 {
+        match mode {
+            PrompterMode::Notch => self.notch_font_size,
+            PrompterMode::Floating => self.floating_font_size,
         }
     }
 }
@@ -0,0 +1,86 @@
+//! Where Whispr keeps its data. Every file path is built from `data_dir()`.
+//!
+//! Resolved once, in this order:
+//! 1. `--data-dir <path>` (or `--data-dir=<path>`) on the command line
+//! 2. the `WHISPR_DATA_DIR` environment variable
+//! 3. portable mode: a `whispr.portable` file next to the executable puts
+//!    the data in a `data` folder beside it, e.g. on a USB stick
+//! 4. the platform data directory, `<data_dir>/Whispr`
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const ENV_VAR: &str = "WHISPR_DATA_DIR";
+const FLAG: &str = "--data-dir";
+const PORTABLE_MARKER: &str = "whispr.portable";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataSource {
+    Flag,
+    Environment,
+    Portable,
+    Default,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DataLocation {
+    pub dir: PathBuf,
+    pub source: DataSource,
+}
+
+pub fn location() -> &'static DataLocation {
+    static LOCATION: OnceLock<DataLocation> = OnceLock::new();
+    LOCATION.get_or_init(resolve)
+}
+
+pub fn data_dir() -> &'static Path {
+    &location().dir
+}
+
+/// `name` inside the data directory.
+pub fn data_file(name: &str) -> PathBuf {
+    data_dir().join(name)
+}
+
+fn resolve() -> DataLocation {
+    if let Some(dir) = flag_value(std::env::args().skip(1)) {
+        return DataLocation {
+            dir: PathBuf::from(dir),
+            source: DataSource::Flag,
+        };
+    }
+    if let Some(dir) = std::env::var_os(ENV_VAR).filter(|v| !v.is_empty()) {
+        return DataLocation {
+            dir: PathBuf::from(dir),
+            source: DataSource::Environment,
+        };
+    }
+    if let Some(exe_dir) = std::env::current_exe().ok().and_then(|p| p.parent().map(Path::to_path_buf)) {
+        if exe_dir.join(PORTABLE_MARKER).exists() {
+            return DataLocation {
+                dir: exe_dir.join("data"),
+                source: DataSource::Portable,
+            };
+        }
+    }
+    DataLocation {
+        dir: dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("Whispr"),
+        source: DataSource::Default,
+    }
+}
+
+fn flag_value(mut args: impl Iterator<Item = String>) -> Option<String> {
+    while let Some(arg) = args.next() {
+        if arg == FLAG {
+            return args.next();
+        }
+        if let Some(value) = arg.strip_prefix(FLAG).and_then(|rest| rest.strip_prefix('=')) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
@@ -0,0 +1,229 @@
+//! Named settings profiles, stored in the `profiles` table next to the
+//! live settings.
+//!
+//! A profile is a full copy of the settings. Activating one copies it into
+//! the live settings; while a profile is active, setting changes are written back
+//! into it so the profile keeps what the user last chose.
+
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::models::Settings;
+use crate::settings;
+use rusqlite::{params, Connection, DatabaseName};
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, Clone)]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    pub settings: Settings,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Profiles {
+    pub active: Option<String>,
+    pub profiles: Vec<Profile>,
+}
+
+/// What the settings window and tray show: names only.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileSummary {
+    pub id: String,
+    pub name: String,
+    pub active: bool,
+}
+
+impl Profiles {
+    pub fn load(conn: &Connection) -> AppResult<Self> {
+        match Self::read_rows(conn) {
+            Ok(profiles) => Ok(profiles),
+            // Opened read-only from before the profiles table existed
+            Err(_) if conn.is_readonly(DatabaseName::Main)? => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn read_rows(conn: &Connection) -> rusqlite::Result<Self> {
+        let mut stmt =
+            conn.prepare("SELECT id, name, settings, active FROM profiles ORDER BY position")?;
+        let rows = stmt.query_map([], |row| {
+            let settings: String = row.get(2)?;
+            Ok((
+                Profile {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    // Profiles saved by an older Whispr go through the same
+                    // migrations as the live settings
+                    settings: match serde_json::from_str(&settings) {
+                        Ok(Value::Object(map)) => settings::upgrade(map),
+                        _ => Settings::default(),
+                    },
+                },
+                row.get::<_, bool>(3)?,
+            ))
+        })?;
+
+        let mut profiles = Self::default();
+        for row in rows {
+            let (profile, active) = row?;
+            if active {
+                profiles.active = Some(profile.id.clone());
+            }
+            profiles.profiles.push(profile);
+        }
+        Ok(profiles)
+    }
+
+    /// Replace the stored profiles with these. Pass a transaction when the
+    /// profiles were loaded from it, so nothing is lost in between.
+    pub fn save(&self, conn: &Connection) -> AppResult<()> {
+        conn.execute("DELETE FROM profiles", [])?;
+        for (position, profile) in self.profiles.iter().enumerate() {
+            conn.execute(
+                "INSERT INTO profiles (id, name, settings, position, active) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    profile.id,
+                    profile.name,
+                    serde_json::to_string(&profile.settings)?,
+                    position as i64,
+                    self.active.as_deref() == Some(profile.id.as_str()),
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn summaries(&self) -> Vec<ProfileSummary> {
+        self.profiles
+            .iter()
+            .map(|p| ProfileSummary {
+                id: p.id.clone(),
+                name: p.name.clone(),
+                active: self.active.as_deref() == Some(p.id.as_str()),
+            })
+            .collect()
+    }
+
+    pub fn get(&self, id: &str) -> AppResult<&Profile> {
+        self.profiles.iter().find(|p| p.id == id).ok_or_else(|| not_found(id))
+    }
+
+    fn get_mut(&mut self, id: &str) -> AppResult<&mut Profile> {
+        self.profiles.iter_mut().find(|p| p.id == id).ok_or_else(|| not_found(id))
+    }
+
+    /// Add a profile holding `settings` and return its id.
+    pub fn create(&mut self, name: &str, settings: Settings) -> AppResult<String> {
+        let name = self.check_name(name, None)?;
+        let id = uuid::Uuid::new_v4().to_string();
+        self.profiles.push(Profile {
+            id: id.clone(),
+            name,
+            settings,
+        });
+        Ok(id)
+    }
+
+    pub fn duplicate(&mut self, id: &str) -> AppResult<String> {
+        let source = self.get(id)?.clone();
+        let name = self.unique_name(&format!("{} Copy", source.name));
+        self.create(&name, source.settings)
+    }
+
+    pub fn rename(&mut self, id: &str, name: &str) -> AppResult<()> {
+        let name = self.check_name(name, Some(id))?;
+        self.get_mut(id)?.name = name;
+        Ok(())
+    }
+
+    pub fn delete(&mut self, id: &str) -> AppResult<()> {
+        self.get(id)?;
+        self.profiles.retain(|p| p.id != id);
+        if self.active.as_deref() == Some(id) {
+            self.active = None;
+        }
+        Ok(())
+    }
+
+    /// Keep the active profile in step with the live settings.
+    pub fn store_active(&mut self, settings: &Settings) -> bool {
+        let Some(active) = self.active.clone() else {
+            return false;
+        };
+        match self.get_mut(&active) {
+            Ok(profile) => {
+                profile.settings = settings.clone();
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn check_name(&self, name: &str, except: Option<&str>) -> AppResult<String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::new(ErrorCode::InvalidData, "Profile name can't be empty"));
+        }
+        let taken = self
+            .profiles
+            .iter()
+            .any(|p| Some(p.id.as_str()) != except && p.name.eq_ignore_ascii_case(name));
+        if taken {
+            return Err(AppError::new(
+                ErrorCode::InvalidData,
+                format!("A profile named \"{}\" already exists", name),
+            ));
+        }
+        Ok(name.to_string())
+    }
+
+    fn unique_name(&self, base: &str) -> String {
+        let taken = |name: &str| self.profiles.iter().any(|p| p.name.eq_ignore_ascii_case(name));
+        if !taken(base) {
+            return base.to_string();
+        }
+        (2..)
+            .map(|n| format!("{} {}", base, n))
+            .find(|name| !taken(name))
+            .unwrap_or_else(|| base.to_string())
+    }
+}
+
+fn not_found(id: &str) -> AppError {
+    AppError::new(ErrorCode::NotFound, "Profile not found").with_details(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::apply_migrations(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn saved_profiles_keep_their_order_and_active_one() {
+        let conn = library();
+        let mut profiles = Profiles::default();
+        profiles
+            .create(
+                "Studio",
+                Settings {
+                    notch_font_size: 24.0,
+                    ..Settings::default()
+                },
+            )
+            .unwrap();
+        let home = profiles.create("Home", Settings::default()).unwrap();
+        profiles.active = Some(home.clone());
+        profiles.save(&conn).unwrap();
+
+        let loaded = Profiles::load(&conn).unwrap();
+        let names: Vec<&str> = loaded.profiles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Studio", "Home"]);
+        assert_eq!(loaded.active, Some(home));
+        assert_eq!(loaded.profiles[0].settings.notch_font_size, 24.0);
+    }
+}
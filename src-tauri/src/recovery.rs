@@ -0,0 +1,141 @@
+use crate::db::Database;
+use crate::state::AppState;
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+
+/// Why the database could not be opened, as shown in the recovery window.
+#[derive(Debug, Clone, Serialize)]
+pub struct StartupFailure {
+    pub message: String,
+    pub detail: String,
+    pub db_path: String,
+    pub backup_available: bool,
+    pub occurred_at: String,
+    pub log_path: String,
+}
+
+/// Managed only when startup failed; `AppState` is managed once recovery succeeds.
+pub struct RecoveryState {
+    pub failure: Mutex<Option<StartupFailure>>,
+}
+
+/// Hide the main window and show the recovery window instead.
+pub fn enter(app: &AppHandle, error: rusqlite::Error) -> Result<(), Box<dyn std::error::Error>> {
+    let failure = record_failure(&error);
+    app.manage(RecoveryState {
+        failure: Mutex::new(Some(failure)),
+    });
+
+    if let Some(w) = app.get_webview_window("main") {
+        w.hide().ok();
+    }
+
+    let win = WebviewWindowBuilder::new(app, "recovery", WebviewUrl::App("recovery.html".into()))
+        .title("Whispr — Database Problem")
+        .inner_size(560.0, 440.0)
+        .center()
+        .resizable(false)
+        .build()?;
+
+    // Closing the recovery window without resolving the problem quits the app,
+    // otherwise it would keep running with no visible window.
+    let app_handle = app.clone();
+    win.on_window_event(move |event| {
+        if let WindowEvent::Destroyed = event {
+            if app_handle.try_state::<AppState>().is_none() {
+                app_handle.exit(1);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Install the recovered database and bring the main window back.
+pub fn finish(app: &AppHandle, db: Database) {
+    let locked = db.vault.is_locked();
+    app.manage(AppState::new(db));
+    crate::sync::resume(app);
+    crate::shortcuts::restore_hold(app);
+    crate::tray::refresh(app);
+
+    if let Some(state) = app.try_state::<RecoveryState>() {
+        if let Ok(mut failure) = state.failure.lock() {
+            *failure = None;
+        }
+    }
+
+    if let Some(w) = app.get_webview_window("main") {
+        // The main window loaded while no database was available
+        w.eval("window.location.reload()").ok();
+        if !locked {
+            w.show().ok();
+            w.set_focus().ok();
+        }
+    }
+    if locked {
+        crate::vault::prompt_unlock(app).ok();
+    }
+    if let Some(w) = app.get_webview_window("recovery") {
+        w.close().ok();
+    }
+}
+
+/// Append the error to the startup log and remember it for the recovery window.
+pub fn record_failure(error: &rusqlite::Error) -> StartupFailure {
+    let db_path = Database::db_path();
+    let log_path = log_path();
+    let occurred_at = chrono::Utc::now().to_rfc3339();
+    let failure = StartupFailure {
+        message: error.to_string(),
+        detail: format!("{:?}", error),
+        db_path: db_path.to_string_lossy().into_owned(),
+        backup_available: Database::backup_path().exists(),
+        occurred_at,
+        log_path: log_path.to_string_lossy().into_owned(),
+    };
+
+    if let Some(parent) = log_path.parent() {
+        std::fs::create_dir_all(parent).ok();
+    }
+    if let Ok(mut file) = std::fs::OpenOptions::new().create(true).append(true).open(&log_path) {
+        writeln!(
+            file,
+            "[{}] failed to open {}: {} ({})",
+            failure.occurred_at, failure.db_path, failure.message, failure.detail
+        )
+        .ok();
+    }
+
+    failure
+}
+
+pub fn log_path() -> PathBuf {
+    crate::paths::data_dir().join("logs").join("startup-errors.log")
+}
+
+/// Rename the database and its WAL/SHM sidecars out of the way,
+/// returning the new location of the database file.
+pub fn move_aside(path: &Path) -> std::io::Result<PathBuf> {
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S");
+    let aside = path.with_extension(format!("db.broken-{}", stamp));
+    std::fs::rename(path, &aside)?;
+
+    for suffix in ["-wal", "-shm"] {
+        let side = sidecar(path, suffix);
+        if side.exists() {
+            std::fs::rename(&side, sidecar(&aside, suffix))?;
+        }
+    }
+
+    Ok(aside)
+}
+
+fn sidecar(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
@@ -0,0 +1,295 @@
+//! Run lists: scripts prompted in order, like the segments of a livestream.
+//!
+//! Each item can pick its own prompter mode and speed and a gap to wait
+//! after it. While a list runs, every script opens set to stop at its end;
+//! when its prompter reports finishing, the list's `end_action` decides
+//! whether the next one opens after the gap or waits for the Next shortcut.
+
+use crate::analysis::{MAX_SPEED, MIN_SPEED};
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::models::{RunList, RunListEndAction, RunListItem, RunListStatus};
+use rusqlite::{params, Connection, DatabaseName};
+use serde_json::Value;
+
+/// Longest gap between two scripts.
+const MAX_GAP_SECONDS: f64 = 3600.0;
+
+pub fn list(conn: &Connection) -> AppResult<Vec<RunList>> {
+    let mut stmt = match conn.prepare(
+        "SELECT id, name, end_action, created_at, updated_at FROM run_lists ORDER BY name COLLATE NOCASE",
+    ) {
+        Ok(stmt) => stmt,
+        // Opened read-only from before the table existed
+        Err(_) if conn.is_readonly(DatabaseName::Main)? => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut lists = stmt
+        .query_map([], |row| {
+            Ok(RunList {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                end_action: serde_json::from_value(Value::String(row.get(2)?))
+                    .unwrap_or(RunListEndAction::Advance),
+                items: Vec::new(),
+                created_at: row.get(3)?,
+                updated_at: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    for list in &mut lists {
+        list.items = items(conn, &list.id)?;
+    }
+    Ok(lists)
+}
+
+pub fn get(conn: &Connection, id: &str) -> AppResult<RunList> {
+    list(conn)?
+        .into_iter()
+        .find(|list| list.id == id)
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, "This run list no longer exists"))
+}
+
+fn items(conn: &Connection, run_list_id: &str) -> AppResult<Vec<RunListItem>> {
+    let mut stmt = conn.prepare(
+        "SELECT i.script_id, s.title, i.mode, i.speed, i.gap_seconds
+         FROM run_list_items i JOIN scripts s ON s.id = i.script_id
+         WHERE i.run_list_id = ?1 ORDER BY i.position",
+    )?;
+    let items = stmt
+        .query_map(params![run_list_id], |row| {
+            Ok(RunListItem {
+                script_id: row.get(0)?,
+                title: row.get(1)?,
+                mode: row
+                    .get::<_, Option<String>>(2)?
+                    .and_then(|mode| serde_json::from_value(Value::String(mode)).ok()),
+                speed: row.get(3)?,
+                gap_seconds: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(items)
+}
+
+fn validate(list: &RunList) -> AppResult<()> {
+    let invalid = |message: &str| Err(AppError::new(ErrorCode::InvalidData, message));
+    if list.name.trim().is_empty() {
+        return invalid("Give the run list a name");
+    }
+    for item in &list.items {
+        if item.speed.is_some_and(|speed| !(MIN_SPEED..=MAX_SPEED).contains(&speed)) {
+            return invalid("Scroll speeds must be between 10 and 200");
+        }
+        if !(0.0..=MAX_GAP_SECONDS).contains(&item.gap_seconds) {
+            return invalid("Gaps must be between 0 seconds and an hour");
+        }
+    }
+    Ok(())
+}
+
+/// Create the list, or replace the one with the same id, items and all.
+/// Fills in the id and timestamps. Run inside a transaction.
+pub fn save(conn: &Connection, list: &mut RunList) -> AppResult<()> {
+    validate(list)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    if list.id.is_empty() {
+        list.id = uuid::Uuid::new_v4().to_string();
+        list.created_at = now.clone();
+    }
+    list.updated_at = now;
+
+    let end_action = serde_json::to_value(list.end_action)?;
+    conn.execute(
+        "INSERT INTO run_lists (id, name, end_action, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(id) DO UPDATE SET
+             name = excluded.name,
+             end_action = excluded.end_action,
+             updated_at = excluded.updated_at",
+        params![list.id, list.name.trim(), end_action.as_str(), list.created_at, list.updated_at],
+    )?;
+    conn.execute("DELETE FROM run_list_items WHERE run_list_id = ?1", params![list.id])?;
+    for (position, item) in list.items.iter().enumerate() {
+        let mode = item.mode.map(serde_json::to_value).transpose()?;
+        conn.execute(
+            "INSERT INTO run_list_items (run_list_id, position, script_id, mode, speed, gap_seconds)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                list.id,
+                position,
+                item.script_id,
+                mode.as_ref().and_then(Value::as_str),
+                item.speed,
+                item.gap_seconds,
+            ],
+        )?;
+    }
+    // Titles, and scripts deleted since the list was loaded
+    list.items = items(conn, &list.id)?;
+    Ok(())
+}
+
+pub fn delete(conn: &Connection, id: &str) -> AppResult<()> {
+    conn.execute("DELETE FROM run_lists WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+/// The run list being prompted.
+pub struct ActiveRun {
+    pub list: RunList,
+    pub index: usize,
+    /// Bumped on every move, so a gap that's still counting down can tell
+    /// it's been overtaken by Next, Previous or another start
+    generation: u64,
+}
+
+impl ActiveRun {
+    pub fn new(list: RunList, index: usize) -> Self {
+        Self {
+            list,
+            index,
+            generation: 0,
+        }
+    }
+
+    pub fn item(&self) -> &RunListItem {
+        &self.list.items[self.index]
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Make `index` current, cancelling any pending move.
+    pub fn go_to(&mut self, index: usize) {
+        self.index = index;
+        self.generation += 1;
+    }
+
+    /// Where to go once the current script finishes, per `end_action`:
+    /// `None` to stay on it.
+    pub fn after_finish(&self) -> Option<usize> {
+        match self.list.end_action {
+            RunListEndAction::Wait => None,
+            RunListEndAction::Advance => self.step(1),
+            RunListEndAction::Loop => Some((self.index + 1) % self.list.items.len()),
+        }
+    }
+
+    /// The item `delta` places away, wrapping around only for a looping list.
+    pub fn step(&self, delta: isize) -> Option<usize> {
+        let count = self.list.items.len() as isize;
+        let index = self.index as isize + delta;
+        if self.list.end_action == RunListEndAction::Loop {
+            Some(index.rem_euclid(count) as usize)
+        } else {
+            (0..count).contains(&index).then_some(index as usize)
+        }
+    }
+
+    pub fn status(&self) -> RunListStatus {
+        let item = self.item();
+        RunListStatus {
+            run_list_id: self.list.id.clone(),
+            name: self.list.name.clone(),
+            index: self.index,
+            count: self.list.items.len(),
+            script_id: item.script_id.clone(),
+            title: item.title.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PrompterMode;
+
+    fn library() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::apply_migrations(&conn).unwrap();
+        for id in ["intro", "segment", "outro"] {
+            conn.execute(
+                "INSERT INTO scripts (id, title, content, created_at, updated_at) VALUES (?1, ?1, '', '', '')",
+                params![id],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    fn item(script_id: &str) -> RunListItem {
+        RunListItem {
+            script_id: script_id.into(),
+            title: String::new(),
+            mode: None,
+            speed: None,
+            gap_seconds: 0.0,
+        }
+    }
+
+    fn run_list(end_action: RunListEndAction) -> RunList {
+        RunList {
+            id: String::new(),
+            name: "Show".into(),
+            end_action,
+            items: vec![item("intro"), item("segment"), item("outro")],
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn saved_lists_keep_their_order_and_settings() {
+        let conn = library();
+        let mut list = run_list(RunListEndAction::Wait);
+        list.items[1].mode = Some(PrompterMode::Floating);
+        list.items[1].speed = Some(80.0);
+        list.items[1].gap_seconds = 5.0;
+        save(&conn, &mut list).unwrap();
+
+        let loaded = get(&conn, &list.id).unwrap();
+        assert_eq!(loaded.end_action, RunListEndAction::Wait);
+        let titles: Vec<_> = loaded.items.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(titles, ["intro", "segment", "outro"]);
+        assert_eq!(loaded.items[1].mode, Some(PrompterMode::Floating));
+        assert_eq!((loaded.items[1].speed, loaded.items[1].gap_seconds), (Some(80.0), 5.0));
+
+        conn.execute("DELETE FROM scripts WHERE id = 'segment'", []).unwrap();
+        assert_eq!(get(&conn, &list.id).unwrap().items.len(), 2);
+        delete(&conn, &list.id).unwrap();
+        let left: i64 = conn
+            .query_row("SELECT COUNT(*) FROM run_list_items", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(left, 0);
+    }
+
+    #[test]
+    fn invalid_items_are_refused() {
+        let conn = library();
+        let mut list = run_list(RunListEndAction::Advance);
+        list.items[0].gap_seconds = -1.0;
+        assert_eq!(save(&conn, &mut list).unwrap_err().code, ErrorCode::InvalidData);
+    }
+
+    #[test]
+    fn end_action_decides_what_follows() {
+        let mut run = ActiveRun::new(run_list(RunListEndAction::Advance), 0);
+        assert_eq!(run.after_finish(), Some(1));
+        run.go_to(2);
+        assert_eq!(run.after_finish(), None);
+        assert_eq!(run.step(1), None);
+        assert_eq!(run.step(-1), Some(1));
+
+        let run = ActiveRun::new(run_list(RunListEndAction::Wait), 0);
+        assert_eq!(run.after_finish(), None);
+        assert_eq!(run.step(-1), None);
+
+        let mut run = ActiveRun::new(run_list(RunListEndAction::Loop), 2);
+        assert_eq!(run.after_finish(), Some(0));
+        assert_eq!(run.step(1), Some(0));
+        run.go_to(0);
+        assert_eq!(run.step(-1), Some(2));
+        assert_eq!(run.generation(), 1);
+    }
+}
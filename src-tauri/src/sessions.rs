@@ -0,0 +1,272 @@
+//! Rehearsal sessions: one row in `sessions` per time a prompter is opened.
+//!
+//! The prompter windows already report every phase change, speed change and
+//! percent of progress (`report_prompter_status`); the session is built from
+//! those reports. Timing is kept here rather than in the window, so a take
+//! is still recorded when its window is closed from the tray or the library
+//! locks. Duration counts scrolling only: the countdown and pauses are left
+//! out.
+//!
+//! The row is written when the session starts, again at every phase change,
+//! and a last time when the prompter closes.
+
+use crate::error::AppResult;
+use crate::models::{PrompterMode, PrompterPhase, Session, SessionStats, TrendPoint};
+use rusqlite::{params, Connection};
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+/// Sessions that reach this far count as run-throughs in the stats.
+const COMPLETE: f64 = 0.99;
+/// How many recent sessions the trend covers.
+const TREND_LENGTH: usize = 20;
+
+pub struct ActiveSession {
+    id: String,
+    script_id: String,
+    mode: PrompterMode,
+    started_at: String,
+    word_count: u32,
+    speed: f64,
+    phase: PrompterPhase,
+    scrolling_since: Option<Instant>,
+    scrolled: Duration,
+    pauses: u32,
+    speed_changes: u32,
+    completion: f64,
+}
+
+impl ActiveSession {
+    pub fn start(script_id: &str, mode: PrompterMode, word_count: u32, speed: f64) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            script_id: script_id.to_string(),
+            mode,
+            started_at: chrono::Utc::now().to_rfc3339(),
+            word_count,
+            speed,
+            phase: PrompterPhase::CountingDown,
+            scrolling_since: None,
+            scrolled: Duration::ZERO,
+            pauses: 0,
+            speed_changes: 0,
+            completion: 0.0,
+        }
+    }
+
+    /// Apply one status report. Returns whether the phase changed, which is
+    /// when the row is worth writing.
+    pub fn report(&mut self, phase: PrompterPhase, progress: f64, speed: f64) -> bool {
+        if speed != self.speed {
+            self.speed_changes += 1;
+            self.speed = speed;
+        }
+        self.completion = self.completion.max(progress.clamp(0.0, 1.0));
+        if phase == self.phase {
+            return false;
+        }
+
+        if phase == PrompterPhase::Scrolling {
+            self.scrolling_since.get_or_insert_with(Instant::now);
+        } else {
+            self.stop_clock();
+            if phase == PrompterPhase::Paused && self.phase == PrompterPhase::Scrolling {
+                self.pauses += 1;
+            }
+        }
+        self.phase = phase;
+        true
+    }
+
+    fn stop_clock(&mut self) {
+        if let Some(since) = self.scrolling_since.take() {
+            self.scrolled += since.elapsed();
+        }
+    }
+
+    fn scrolled(&self) -> Duration {
+        self.scrolled + self.scrolling_since.map_or(Duration::ZERO, |since| since.elapsed())
+    }
+
+    /// Words per minute actually covered, once there's enough to measure.
+    fn actual_wpm(&self) -> Option<f64> {
+        let minutes = self.scrolled().as_secs_f64() / 60.0;
+        (minutes >= 1.0 / 60.0).then(|| self.completion * self.word_count as f64 / minutes)
+    }
+
+    /// Write the session as it stands. `ended` marks it finished.
+    pub fn save(&self, conn: &Connection, ended: bool) -> AppResult<()> {
+        let ended_at = ended.then(|| chrono::Utc::now().to_rfc3339());
+        let mode = serde_json::to_value(self.mode)?;
+        conn.execute(
+            "INSERT INTO sessions (id, script_id, mode, started_at, ended_at, duration_seconds,
+                                   pauses, speed_changes, completion, word_count, actual_wpm)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+             ON CONFLICT(id) DO UPDATE SET
+                 ended_at = excluded.ended_at,
+                 duration_seconds = excluded.duration_seconds,
+                 pauses = excluded.pauses,
+                 speed_changes = excluded.speed_changes,
+                 completion = excluded.completion,
+                 actual_wpm = excluded.actual_wpm",
+            params![
+                self.id,
+                self.script_id,
+                mode.as_str(),
+                self.started_at,
+                ended_at,
+                self.scrolled().as_secs_f64(),
+                self.pauses,
+                self.speed_changes,
+                self.completion,
+                self.word_count,
+                self.actual_wpm(),
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+/// A script's sessions, newest first.
+pub fn list(conn: &Connection, script_id: &str, limit: u32) -> AppResult<Vec<Session>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, script_id, mode, started_at, ended_at, duration_seconds, pauses,
+                speed_changes, completion, word_count, actual_wpm
+         FROM sessions WHERE script_id = ?1 ORDER BY started_at DESC LIMIT ?2",
+    )?;
+    let sessions = stmt
+        .query_map(params![script_id, limit], |row| {
+            Ok(Session {
+                id: row.get(0)?,
+                script_id: row.get(1)?,
+                mode: serde_json::from_value(Value::String(row.get(2)?))
+                    .unwrap_or(PrompterMode::Floating),
+                started_at: row.get(3)?,
+                ended_at: row.get(4)?,
+                duration_seconds: row.get(5)?,
+                pauses: row.get(6)?,
+                speed_changes: row.get(7)?,
+                completion: row.get(8)?,
+                word_count: row.get(9)?,
+                actual_wpm: row.get(10)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(sessions)
+}
+
+/// Averages over the sessions that got past the countdown, and the trend of
+/// the last few run-throughs, oldest first. `sessions` is newest first, as
+/// `list` returns them.
+pub fn stats(sessions: &[Session]) -> SessionStats {
+    let takes: Vec<&Session> = sessions.iter().filter(|s| s.duration_seconds > 0.0).collect();
+    let complete: Vec<&Session> = takes.iter().copied().filter(|s| s.completion >= COMPLETE).collect();
+
+    let trend: Vec<TrendPoint> = complete
+        .iter()
+        .take(TREND_LENGTH)
+        .rev()
+        .map(|s| TrendPoint {
+            started_at: s.started_at.clone(),
+            duration_seconds: s.duration_seconds,
+            actual_wpm: s.actual_wpm,
+            pauses: s.pauses,
+        })
+        .collect();
+    let durations: Vec<f64> = trend.iter().map(|p| p.duration_seconds).collect();
+    let wpms: Vec<f64> = trend.iter().filter_map(|p| p.actual_wpm).collect();
+
+    SessionStats {
+        sessions: takes.len() as u32,
+        completed: complete.len() as u32,
+        average_duration_seconds: average(complete.iter().map(|s| s.duration_seconds)),
+        average_wpm: average(takes.iter().filter_map(|s| s.actual_wpm)),
+        average_completion: average(takes.iter().map(|s| s.completion)),
+        average_pauses: average(takes.iter().map(|s| s.pauses as f64)),
+        duration_trend: slope(&durations),
+        wpm_trend: slope(&wpms),
+        trend,
+    }
+}
+
+fn average(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+/// Least-squares change per session, or `None` with fewer than two.
+fn slope(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let n = values.len() as f64;
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = values.iter().sum::<f64>() / n;
+    let (mut covariance, mut variance) = (0.0, 0.0);
+    for (x, y) in values.iter().enumerate() {
+        let dx = x as f64 - mean_x;
+        covariance += dx * (y - mean_y);
+        variance += dx * dx;
+    }
+    Some(covariance / variance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(started_at: &str, duration_seconds: f64, completion: f64, actual_wpm: Option<f64>) -> Session {
+        Session {
+            id: started_at.into(),
+            script_id: "s".into(),
+            mode: PrompterMode::Floating,
+            started_at: started_at.into(),
+            ended_at: None,
+            duration_seconds,
+            pauses: 1,
+            speed_changes: 0,
+            completion,
+            word_count: 300,
+            actual_wpm,
+        }
+    }
+
+    #[test]
+    fn pauses_and_speed_changes_are_counted() {
+        let mut active = ActiveSession::start("s", PrompterMode::Notch, 100, 50.0);
+        assert!(active.report(PrompterPhase::Scrolling, 0.0, 50.0));
+        assert!(!active.report(PrompterPhase::Scrolling, 0.2, 60.0));
+        assert!(active.report(PrompterPhase::Paused, 0.3, 60.0));
+        assert!(active.report(PrompterPhase::Scrolling, 0.3, 60.0));
+        assert!(active.report(PrompterPhase::Finished, 1.0, 60.0));
+        active.report(PrompterPhase::Scrolling, 0.1, 60.0);
+        assert_eq!((active.pauses, active.speed_changes), (1, 1));
+        assert_eq!(active.completion, 1.0);
+    }
+
+    #[test]
+    fn stats_average_takes_and_show_the_trend() {
+        // Newest first, getting faster
+        let sessions = [
+            session("4", 150.0, 1.0, Some(160.0)),
+            session("3", 0.0, 0.0, None),
+            session("2", 170.0, 1.0, Some(150.0)),
+            session("1", 60.0, 0.4, Some(120.0)),
+            session("0", 190.0, 1.0, Some(140.0)),
+        ];
+        let stats = stats(&sessions);
+        assert_eq!((stats.sessions, stats.completed), (4, 3));
+        assert_eq!(stats.average_duration_seconds, Some(170.0));
+        assert_eq!(stats.average_wpm, Some(142.5));
+        assert_eq!(stats.duration_trend, Some(-20.0));
+        assert_eq!(stats.wpm_trend, Some(10.0));
+        assert_eq!(stats.trend.first().map(|p| p.started_at.as_str()), Some("0"));
+    }
+
+    #[test]
+    fn no_trend_from_a_single_take() {
+        let stats = stats(&[session("0", 100.0, 1.0, None)]);
+        assert_eq!(stats.duration_trend, None);
+        assert_eq!(stats.average_wpm, None);
+    }
+}
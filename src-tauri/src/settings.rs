@@ -0,0 +1,653 @@
+//! Loading and saving settings, stored one key per row in the `settings`
+//! table with every change recorded in `settings_log`.
+//!
+//! Settings are versioned. Older values are brought up to date by the
+//! migration chain below, missing keys take their defaults and unknown keys
+//! are ignored, so adding a field never breaks what is stored. Settings
+//! from before the table existed are imported once from settings.json.
+//!
+//! `schema()` describes every setting's type, range and allowed values; it is
+//! also what `validate` checks against.
+
+use crate::error::{AppError, AppResult, ErrorCode, FieldError};
+use crate::files;
+use crate::models::{
+    AppearanceMode, EndAction, FloatingTheme, FontFamily, GlowStyle, NotchPosition, PrompterMode,
+    Settings, SETTINGS_VERSION,
+};
+use rusqlite::{params, Connection, DatabaseName};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingSchema {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub group: &'static str,
+    #[serde(flatten)]
+    pub kind: SettingKind,
+    pub default: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SettingKind {
+    Number { min: f64, max: f64 },
+    Integer { min: u32, max: u32 },
+    Boolean,
+    Color,
+    /// A global keyboard shortcut such as `Ctrl+Shift+Space`
+    Shortcut,
+    Enum { options: Vec<EnumOption> },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EnumOption {
+    pub value: &'static str,
+    pub label: &'static str,
+}
+
+/// `MIGRATIONS[n]` upgrades version `n` settings to version `n + 1`.
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[
+    // 0 → 1: files written before settings were versioned. Same shape.
+    |_| {},
+];
+
+const _: () = assert!(MIGRATIONS.len() == SETTINGS_VERSION as usize);
+
+/// Entries kept in `settings_log`; older ones are dropped as new ones arrive.
+const LOG_LIMIT: i64 = 1000;
+
+/// Where settings lived before they moved into the database.
+fn legacy_path() -> PathBuf {
+    crate::paths::data_file("settings.json")
+}
+
+/// One entry of the settings change log.
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingChange {
+    pub key: String,
+    pub old_value: Option<Value>,
+    pub new_value: Value,
+    pub source: String,
+    pub changed_at: String,
+}
+
+/// Read the live settings. The first time, an existing settings.json is
+/// imported and kept as `settings.json.imported`.
+///
+/// A read-only library is never written to; settings then come from what
+/// is stored, or from the old file when nothing is.
+pub fn load(conn: &Connection) -> AppResult<Settings> {
+    let read_only = conn.is_readonly(DatabaseName::Main)?;
+    let map = match read_rows(conn) {
+        Ok(map) => map,
+        // Opened read-only from before the settings table existed
+        Err(_) if read_only => Map::new(),
+        Err(e) => return Err(e.into()),
+    };
+
+    if map.is_empty() {
+        let legacy = legacy_path();
+        let settings = read_legacy(&legacy).map(upgrade).unwrap_or_default();
+        if !read_only {
+            store(conn, None, &settings, "import")?;
+            if legacy.exists() {
+                std::fs::rename(&legacy, legacy.with_extension("json.imported"))?;
+            }
+        }
+        return Ok(settings);
+    }
+
+    let version = file_version(&map);
+    let settings = upgrade(map);
+    // Settings from a newer Whispr are read as far as we understand them and left alone
+    if version < SETTINGS_VERSION && !read_only {
+        store(conn, None, &settings, "upgrade")?;
+    }
+    Ok(settings)
+}
+
+/// Bring a stored settings object of any version up to the current format.
+/// Also used for the copies of settings kept in profiles.
+pub fn upgrade(mut map: Map<String, Value>) -> Settings {
+    if file_version(&map) <= SETTINGS_VERSION {
+        run_migrations(&mut map);
+    }
+    sanitize(from_map(map))
+}
+
+/// Bring a partial settings object, such as an imported file, up to the
+/// current format. Unlike stored settings, values from a newer Whispr are
+/// refused: they may not mean what this version thinks they do.
+pub fn migrate(map: &mut Map<String, Value>) -> AppResult<()> {
+    if file_version(map) > SETTINGS_VERSION {
+        return Err(AppError::new(
+            ErrorCode::InvalidData,
+            "These settings were made by a newer version of Whispr",
+        ));
+    }
+    run_migrations(map);
+    Ok(())
+}
+
+fn run_migrations(map: &mut Map<String, Value>) {
+    for migrate in &MIGRATIONS[file_version(map) as usize..] {
+        migrate(map);
+    }
+    map.insert("version".into(), SETTINGS_VERSION.into());
+}
+
+fn read_rows(conn: &Connection) -> rusqlite::Result<Map<String, Value>> {
+    let mut stmt = conn.prepare("SELECT key, value FROM settings")?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+
+    let mut map = Map::new();
+    for row in rows {
+        let (key, value) = row?;
+        // A value that isn't JSON takes its default, like any other bad value
+        if let Ok(value) = serde_json::from_str(&value) {
+            map.insert(key, value);
+        }
+    }
+    Ok(map)
+}
+
+/// The old settings.json, or its `.bak` when the file itself is unreadable.
+fn read_legacy(path: &Path) -> Option<Map<String, Value>> {
+    read_object(path).or_else(|| read_object(&files::backup_path(path)))
+}
+
+fn read_object(path: &Path) -> Option<Map<String, Value>> {
+    let data = std::fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&data).ok()? {
+        Value::Object(map) => Some(map),
+        _ => None,
+    }
+}
+
+fn file_version(map: &Map<String, Value>) -> u32 {
+    map.get("version").and_then(Value::as_u64).unwrap_or(0) as u32
+}
+
+/// Write every key whose value differs from `previous` (all keys when
+/// `None`) and record each change in the log, tagged with `source`.
+///
+/// Takes any connection, so a caller can pass a transaction that also
+/// changes scripts; on its own, the keys are written all together or not
+/// at all. Returns the keys written.
+pub fn store(
+    conn: &Connection,
+    previous: Option<&Settings>,
+    settings: &Settings,
+    source: &str,
+) -> AppResult<Vec<String>> {
+    let before = match previous.map(serde_json::to_value).transpose()? {
+        Some(Value::Object(map)) => map,
+        _ => Map::new(),
+    };
+    let Value::Object(after) = serde_json::to_value(settings)? else {
+        return Err(AppError::new(ErrorCode::Internal, "Settings are not an object"));
+    };
+    let changed: Vec<(String, Value)> = after
+        .into_iter()
+        .filter(|(key, value)| before.get(key) != Some(value))
+        .collect();
+    if changed.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute_batch("SAVEPOINT store_settings")?;
+    let result = (|| -> AppResult<()> {
+        for (key, value) in &changed {
+            let new_value = value.to_string();
+            conn.execute(
+                "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+                params![key, new_value, now],
+            )?;
+            conn.execute(
+                "INSERT INTO settings_log (key, old_value, new_value, source, changed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![key, before.get(key).map(Value::to_string), new_value, source, now],
+            )?;
+        }
+        conn.execute(
+            "DELETE FROM settings_log WHERE id <= (SELECT MAX(id) FROM settings_log) - ?1",
+            params![LOG_LIMIT],
+        )?;
+        Ok(())
+    })();
+    match result {
+        Ok(()) => conn.execute_batch("RELEASE store_settings")?,
+        Err(e) => {
+            conn.execute_batch("ROLLBACK TO store_settings; RELEASE store_settings")?;
+            return Err(e);
+        }
+    }
+
+    Ok(changed.into_iter().map(|(key, _)| key).collect())
+}
+
+/// Most recent changes first, optionally for one key only.
+pub fn history(conn: &Connection, key: Option<&str>, limit: u32) -> AppResult<Vec<SettingChange>> {
+    let mut stmt = conn.prepare(
+        "SELECT key, old_value, new_value, source, changed_at FROM settings_log
+         WHERE ?1 IS NULL OR key = ?1 ORDER BY id DESC LIMIT ?2",
+    )?;
+    let parse = |text: String| serde_json::from_str(&text).unwrap_or(Value::String(text));
+    let rows = stmt.query_map(params![key, limit], |row| {
+        Ok(SettingChange {
+            key: row.get(0)?,
+            old_value: row.get::<_, Option<String>>(1)?.map(parse),
+            new_value: parse(row.get(2)?),
+            source: row.get(3)?,
+            changed_at: row.get(4)?,
+        })
+    })?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+/// Held across every change to the live settings, from the read to
+/// registering the hold key after the commit, so two windows changing
+/// settings at once can't lose or undo either change. Taken
+/// before the database connection, never while holding it.
+pub fn write_lock() -> MutexGuard<'static, ()> {
+    static WRITE_LOCK: Mutex<()> = Mutex::new(());
+    WRITE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+pub fn schema() -> Vec<SettingSchema> {
+    use SettingKind::*;
+
+    let entries = [
+        ("notch_font_size", "Font Size", "notch", Number { min: 14.0, max: 26.0 }),
+        ("notch_font_family", "Font Family", "notch", enum_kind(FontFamily::OPTIONS)),
+        ("notch_line_count", "Line Count", "notch", Integer { min: 1, max: 3 }),
+        ("notch_scroll_speed", "Speed", "notch", Number { min: 10.0, max: 200.0 }),
+        ("notch_width_percent", "Width", "notch", Number { min: 30.0, max: 80.0 }),
+        ("notch_height", "Height", "notch", Number { min: 60.0, max: 140.0 }),
+        ("notch_opacity", "Opacity", "notch", Number { min: 0.4, max: 1.0 }),
+        ("notch_show_timer", "Show Timer", "notch", Boolean),
+        ("notch_glow_style", "Glow Style", "notch", enum_kind(GlowStyle::OPTIONS)),
+        ("notch_position", "Position", "notch", enum_kind(NotchPosition::OPTIONS)),
+        ("floating_font_size", "Font Size", "floating", Number { min: 18.0, max: 54.0 }),
+        ("floating_font_family", "Font Family", "floating", enum_kind(FontFamily::OPTIONS)),
+        ("text_color_hex", "Text Color", "floating", Color),
+        ("mirror_mode", "Mirror Mode", "floating", Boolean),
+        ("floating_scroll_speed", "Speed", "floating", Number { min: 10.0, max: 200.0 }),
+        ("floating_theme", "Theme", "floating", enum_kind(FloatingTheme::OPTIONS)),
+        ("appearance_mode", "Appearance", "general", enum_kind(AppearanceMode::OPTIONS)),
+        ("prompter_mode", "Default Mode", "general", enum_kind(PrompterMode::OPTIONS)),
+        ("countdown_seconds", "Countdown", "general", Integer { min: 0, max: 10 }),
+        ("end_action", "End of Script", "general", enum_kind(EndAction::OPTIONS)),
+        ("show_menu_bar_icon", "Show Menu Bar Icon", "general", Boolean),
+        ("hold_to_scroll", "Hold to Scroll", "general", Boolean),
+        ("hold_shortcut", "Hold Key", "general", Shortcut),
+    ];
+
+    let defaults = match serde_json::to_value(Settings::default()) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    };
+    entries
+        .into_iter()
+        .map(|(key, label, group, kind)| SettingSchema {
+            key,
+            label,
+            group,
+            kind,
+            default: defaults.get(key).cloned().unwrap_or(Value::Null),
+        })
+        .collect()
+}
+
+/// Apply `changes` (setting key → JSON value) on top of `settings` through
+/// their serde representation. All changes are checked before any is
+/// applied, so a batch either succeeds as a whole or not at all.
+///
+/// Strings are accepted for numbers and booleans (`"20"`, `"true"`), which is
+/// what HTML inputs produce.
+pub fn patch(settings: &Settings, changes: &Map<String, Value>) -> AppResult<Settings> {
+    let Value::Object(mut values) = serde_json::to_value(settings)? else {
+        return Err(AppError::new(ErrorCode::Internal, "Settings are not an object"));
+    };
+
+    let mut errors = Vec::new();
+    for (key, value) in changes {
+        let Some(current) = values.get(key).filter(|_| key != "version") else {
+            errors.push(FieldError {
+                key: key.clone(),
+                message: format!("Unknown setting key: {}", key),
+            });
+            continue;
+        };
+
+        let value = coerce(current, value);
+        let mut candidate = values.clone();
+        candidate.insert(key.clone(), value.clone());
+        if let Err(e) = serde_json::from_value::<Settings>(Value::Object(candidate)) {
+            errors.push(FieldError {
+                key: key.clone(),
+                message: format!("Invalid value for {}: {}", key, e),
+            });
+            continue;
+        }
+        values.insert(key.clone(), value);
+    }
+    if !errors.is_empty() {
+        return Err(AppError::invalid_settings(errors));
+    }
+
+    let patched: Settings = serde_json::from_value(Value::Object(values))?;
+    validate(&patched)?;
+    Ok(patched)
+}
+
+/// Keys whose values differ between two versions of the settings.
+pub fn changed_keys(before: &Settings, after: &Settings) -> Vec<String> {
+    let (Ok(Value::Object(before)), Ok(Value::Object(after))) =
+        (serde_json::to_value(before), serde_json::to_value(after))
+    else {
+        return Vec::new();
+    };
+    after
+        .into_iter()
+        .filter(|(key, value)| before.get(key) != Some(value))
+        .map(|(key, _)| key)
+        .collect()
+}
+
+fn coerce(current: &Value, value: &Value) -> Value {
+    match (current, value) {
+        (Value::Number(_) | Value::Bool(_), Value::String(text)) => {
+            match serde_json::from_str::<Value>(text.trim()) {
+                Ok(parsed @ (Value::Number(_) | Value::Bool(_))) => parsed,
+                _ => value.clone(),
+            }
+        }
+        _ => value.clone(),
+    }
+}
+
+/// Reject out-of-range numbers, malformed colors and unusable shortcuts.
+/// Enumerations are already checked by their types.
+pub fn validate(settings: &Settings) -> AppResult<()> {
+    let errors = field_errors(settings);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::invalid_settings(errors))
+    }
+}
+
+fn field_errors(settings: &Settings) -> Vec<FieldError> {
+    let Ok(Value::Object(values)) = serde_json::to_value(settings) else {
+        return Vec::new();
+    };
+
+    let mut errors = Vec::new();
+    for entry in schema() {
+        let value = values.get(entry.key);
+        let message = match entry.kind {
+            SettingKind::Number { min, max } => value
+                .and_then(Value::as_f64)
+                .filter(|v| *v < min || *v > max)
+                .map(|_| format!("{} must be between {} and {}", entry.label, min, max)),
+            SettingKind::Integer { min, max } => value
+                .and_then(Value::as_u64)
+                .filter(|v| *v < min as u64 || *v > max as u64)
+                .map(|_| format!("{} must be between {} and {}", entry.label, min, max)),
+            SettingKind::Color => value
+                .and_then(Value::as_str)
+                .filter(|v| !is_hex_color(v))
+                .map(|_| format!("{} must be a color like #FFFFFF", entry.label)),
+            SettingKind::Shortcut => value
+                .and_then(Value::as_str)
+                .and_then(|v| crate::shortcuts::parse_hold(v).err())
+                .map(|e| e.message),
+            _ => None,
+        };
+        if let Some(message) = message {
+            errors.push(FieldError {
+                key: entry.key.to_string(),
+                message,
+            });
+        }
+    }
+    errors
+}
+
+fn enum_kind(options: &[(&'static str, &'static str)]) -> SettingKind {
+    SettingKind::Enum {
+        options: options
+            .iter()
+            .map(|&(value, label)| EnumOption { value, label })
+            .collect(),
+    }
+}
+
+fn is_hex_color(value: &str) -> bool {
+    value.len() == 7
+        && value.starts_with('#')
+        && value[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Put any field that fails validation back to its default, so a hand-edited
+/// file can't push the prompter out of range.
+fn sanitize(settings: Settings) -> Settings {
+    let errors = field_errors(&settings);
+    if errors.is_empty() {
+        return settings;
+    }
+    let (Ok(Value::Object(mut values)), Ok(Value::Object(defaults))) = (
+        serde_json::to_value(&settings),
+        serde_json::to_value(Settings::default()),
+    ) else {
+        return settings;
+    };
+    for error in errors {
+        if let Some(default) = defaults.get(&error.key) {
+            values.insert(error.key, default.clone());
+        }
+    }
+    serde_json::from_value(Value::Object(values)).unwrap_or(settings)
+}
+
+/// Deserialize, keeping the default for any key whose value has the wrong
+/// type instead of rejecting the whole file.
+fn from_map(map: Map<String, Value>) -> Settings {
+    if let Ok(settings) = serde_json::from_value(Value::Object(map.clone())) {
+        return settings;
+    }
+
+    let mut merged = match serde_json::to_value(Settings::default()) {
+        Ok(Value::Object(defaults)) => defaults,
+        _ => return Settings::default(),
+    };
+    for (key, value) in map {
+        let previous = merged.insert(key.clone(), value);
+        if serde_json::from_value::<Settings>(Value::Object(merged.clone())).is_err() {
+            match previous {
+                Some(previous) => merged.insert(key, previous),
+                None => merged.remove(&key),
+            };
+        }
+    }
+    serde_json::from_value(Value::Object(merged)).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn object(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => panic!("not an object"),
+        }
+    }
+
+    fn library() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::apply_migrations(&conn).unwrap();
+        conn
+    }
+
+    fn error_keys(error: AppError) -> Vec<String> {
+        assert_eq!(error.code, ErrorCode::InvalidSetting);
+        let fields = error.details.unwrap();
+        let mut keys: Vec<String> = fields
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| field["key"].as_str().unwrap().to_string())
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    fn log_count(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM settings_log", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn unversioned_files_are_upgraded() {
+        let settings = upgrade(object(json!({
+            "notch_font_size": 24.0,
+            "floating_theme": "light",
+            "mirror_mode": true,
+        })));
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.notch_font_size, 24.0);
+        assert_eq!(settings.floating_theme, FloatingTheme::Light);
+        assert!(settings.mirror_mode);
+        // Everything else takes its default
+        assert_eq!(settings.countdown_seconds, Settings::default().countdown_seconds);
+    }
+
+    #[test]
+    fn newer_versions_are_read_but_never_rewritten() {
+        let conn = library();
+        let newer = Settings {
+            version: SETTINGS_VERSION + 1,
+            notch_font_size: 22.0,
+            ..Settings::default()
+        };
+        store(&conn, None, &newer, "test").unwrap();
+        let logged = log_count(&conn);
+
+        let settings = load(&conn).unwrap();
+        assert_eq!(settings.version, SETTINGS_VERSION + 1);
+        assert_eq!(settings.notch_font_size, 22.0);
+        assert_eq!(log_count(&conn), logged);
+    }
+
+    #[test]
+    fn damaged_values_fall_back_to_their_defaults() {
+        let defaults = Settings::default();
+        let settings = upgrade(object(json!({
+            "version": SETTINGS_VERSION,
+            "notch_font_size": "big",
+            "notch_glow_style": "sparkly",
+            "notch_position": "middle",
+            "floating_font_size": 500.0,
+            "text_color_hex": "white",
+            "mirror_mode": true,
+            "no_such_key": 1,
+        })));
+        assert_eq!(settings.notch_font_size, defaults.notch_font_size);
+        assert_eq!(settings.notch_glow_style, defaults.notch_glow_style);
+        assert_eq!(settings.notch_position, defaults.notch_position);
+        assert_eq!(settings.floating_font_size, defaults.floating_font_size);
+        assert_eq!(settings.text_color_hex, defaults.text_color_hex);
+        assert!(settings.mirror_mode);
+    }
+
+    #[test]
+    fn patches_coerce_form_strings() {
+        let patched = patch(
+            &Settings::default(),
+            &object(json!({
+                "notch_font_size": "22",
+                "countdown_seconds": " 5 ",
+                "mirror_mode": "true",
+                "floating_theme": "greenScreen",
+                "notch_position": "bottom",
+            })),
+        )
+        .unwrap();
+        assert_eq!(patched.notch_font_size, 22.0);
+        assert_eq!(patched.countdown_seconds, 5);
+        assert!(patched.mirror_mode);
+        assert_eq!(patched.floating_theme, FloatingTheme::GreenScreen);
+        assert_eq!(patched.notch_position, NotchPosition::Bottom);
+        assert_eq!(changed_keys(&Settings::default(), &patched).len(), 5);
+    }
+
+    #[test]
+    fn patches_are_rejected_as_a_whole() {
+        let error = patch(
+            &Settings::default(),
+            &object(json!({
+                "notch_font_size": "abc",
+                "mirror_mode": true,
+                "no_such_key": 1,
+                "version": 7,
+                "countdown_seconds": -1,
+            })),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error_keys(error),
+            ["countdown_seconds", "no_such_key", "notch_font_size", "version"]
+        );
+    }
+
+    #[test]
+    fn each_kind_of_setting_is_validated() {
+        let mut settings = Settings::default();
+        assert!(validate(&settings).is_ok());
+
+        settings.notch_opacity = 0.1;
+        settings.notch_line_count = 4;
+        settings.text_color_hex = "#12345G".into();
+        settings.hold_shortcut = "Not A Key".into();
+        assert_eq!(
+            error_keys(validate(&settings).unwrap_err()),
+            ["hold_shortcut", "notch_line_count", "notch_opacity", "text_color_hex"]
+        );
+
+        // A fixed shortcut can't double as the hold key
+        let settings = Settings {
+            hold_shortcut: crate::shortcuts::PAUSE.into(),
+            ..Settings::default()
+        };
+        assert_eq!(error_keys(validate(&settings).unwrap_err()), ["hold_shortcut"]);
+    }
+
+    #[test]
+    fn stored_changes_are_logged() {
+        let conn = library();
+        let defaults = Settings::default();
+        let written = store(&conn, None, &defaults, "import").unwrap();
+        assert_eq!(written.len() as i64, log_count(&conn));
+        assert!(history(&conn, None, 100).unwrap().iter().all(|c| c.old_value.is_none()));
+
+        let mut changed = defaults.clone();
+        changed.notch_font_size = 24.0;
+        assert_eq!(store(&conn, Some(&defaults), &changed, "settings").unwrap(), ["notch_font_size"]);
+        assert!(store(&conn, Some(&changed), &changed, "settings").unwrap().is_empty());
+
+        let latest = &history(&conn, Some("notch_font_size"), 10).unwrap()[0];
+        assert_eq!(latest.old_value, Some(json!(20.0)));
+        assert_eq!(latest.new_value, json!(24.0));
+        assert_eq!(latest.source, "settings");
+        assert_eq!(load(&conn).unwrap().notch_font_size, 24.0);
+    }
+}
@@ -0,0 +1,253 @@
+//! Settings files that can be handed to someone else: export, preview and
+//! import, optionally limited to some setting groups ("notch", "floating",
+//! "general").
+
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::files;
+use crate::models::Settings;
+use crate::settings;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+/// Bumped if the file layout (not the settings inside it) changes.
+const EXPORT_FORMAT: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportFile {
+    pub whispr_settings: u32,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub exported_at: Option<String>,
+    pub settings: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingDiff {
+    pub key: String,
+    pub label: String,
+    pub group: String,
+    pub current: Value,
+    pub incoming: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportPreview {
+    pub name: Option<String>,
+    pub changes: Vec<SettingDiff>,
+    /// Keys in the file this version of Whispr doesn't know
+    pub ignored: Vec<String>,
+}
+
+pub fn export(path: &Path, name: Option<String>, settings: &Settings) -> AppResult<()> {
+    let Value::Object(map) = serde_json::to_value(settings)? else {
+        return Err(AppError::new(ErrorCode::Internal, "Settings are not an object"));
+    };
+
+    let file = ExportFile {
+        whispr_settings: EXPORT_FORMAT,
+        name,
+        exported_at: Some(chrono::Utc::now().to_rfc3339()),
+        settings: map,
+    };
+    let data = serde_json::to_string_pretty(&file)?;
+    files::write_atomic(path, data.as_bytes())?;
+    Ok(())
+}
+
+pub fn read(path: &Path) -> AppResult<ExportFile> {
+    let data = std::fs::read_to_string(path)?;
+    let file: ExportFile = serde_json::from_str(&data).map_err(|e| {
+        AppError::new(ErrorCode::InvalidData, "This is not a Whispr settings file")
+            .with_details(e.to_string())
+    })?;
+    if file.whispr_settings > EXPORT_FORMAT {
+        return Err(AppError::new(
+            ErrorCode::InvalidData,
+            "This settings file was made by a newer version of Whispr",
+        ));
+    }
+    Ok(file)
+}
+
+/// Apply the file's values in `groups` (all groups when `None`) on top of
+/// `current`, with the same validation as any other settings change. The
+/// file is upgraded to the current settings version first.
+/// Returns the result and what it changes.
+pub fn merge(
+    current: &Settings,
+    file: &ExportFile,
+    groups: Option<&[String]>,
+) -> AppResult<(Settings, ImportPreview)> {
+    let schema = settings::schema();
+    if let Some(unknown) = groups
+        .into_iter()
+        .flatten()
+        .find(|group| !schema.iter().any(|entry| entry.group == group.as_str()))
+    {
+        return Err(AppError::new(
+            ErrorCode::InvalidData,
+            format!("There is no setting group called \"{}\"", unknown),
+        ));
+    }
+
+    let mut incoming = file.settings.clone();
+    settings::migrate(&mut incoming)?;
+
+    let mut changes = Map::new();
+    let mut ignored = Vec::new();
+
+    for (key, value) in &incoming {
+        match schema.iter().find(|entry| entry.key == key) {
+            Some(entry) => {
+                if groups.is_none_or(|groups| groups.iter().any(|g| g == entry.group)) {
+                    changes.insert(key.clone(), value.clone());
+                }
+            }
+            None if key == "version" => {}
+            None => ignored.push(key.clone()),
+        }
+    }
+
+    let merged = settings::patch(current, &changes)?;
+    let (Value::Object(before), Value::Object(after)) =
+        (serde_json::to_value(current)?, serde_json::to_value(&merged)?)
+    else {
+        return Err(AppError::new(ErrorCode::Internal, "Settings are not an object"));
+    };
+
+    let changes = settings::changed_keys(current, &merged)
+        .into_iter()
+        .filter_map(|key| {
+            let entry = schema.iter().find(|entry| entry.key == key)?;
+            Some(SettingDiff {
+                label: entry.label.to_string(),
+                group: entry.group.to_string(),
+                current: before.get(&key).cloned().unwrap_or(Value::Null),
+                incoming: after.get(&key).cloned().unwrap_or(Value::Null),
+                key,
+            })
+        })
+        .collect();
+
+    Ok((
+        merged,
+        ImportPreview {
+            name: file.name.clone(),
+            changes,
+            ignored,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{FloatingTheme, SETTINGS_VERSION};
+    use serde_json::json;
+
+    fn file(settings: Value) -> ExportFile {
+        let Value::Object(settings) = settings else {
+            panic!("not an object");
+        };
+        ExportFile {
+            whispr_settings: EXPORT_FORMAT,
+            name: Some("Studio".into()),
+            exported_at: None,
+            settings,
+        }
+    }
+
+    fn groups(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn exports_keep_the_settings_version() {
+        let path = std::env::temp_dir().join(format!("whispr-export-{}.json", uuid::Uuid::new_v4()));
+        export(&path, None, &Settings::default()).unwrap();
+        let exported = read(&path);
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(exported.unwrap().settings["version"], json!(SETTINGS_VERSION));
+    }
+
+    #[test]
+    fn only_the_chosen_groups_are_imported() {
+        let incoming = file(json!({
+            "version": SETTINGS_VERSION,
+            "notch_font_size": 24.0,
+            "floating_theme": "light",
+        }));
+
+        let (merged, preview) =
+            merge(&Settings::default(), &incoming, Some(&groups(&["floating"]))).unwrap();
+        assert_eq!(merged.notch_font_size, Settings::default().notch_font_size);
+        assert_eq!(merged.floating_theme, FloatingTheme::Light);
+        assert_eq!(preview.changes.len(), 1);
+
+        let (merged, _) = merge(&Settings::default(), &incoming, None).unwrap();
+        assert_eq!(merged.notch_font_size, 24.0);
+        assert_eq!(merged.floating_theme, FloatingTheme::Light);
+    }
+
+    #[test]
+    fn unknown_groups_are_rejected() {
+        let incoming = file(json!({ "notch_font_size": 24.0 }));
+        let error = merge(&Settings::default(), &incoming, Some(&groups(&["notch "]))).unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidData);
+    }
+
+    #[test]
+    fn unknown_keys_are_listed_as_ignored() {
+        let incoming = file(json!({
+            "version": SETTINGS_VERSION,
+            "notch_font_size": 24.0,
+            "sparkles": true,
+        }));
+        let (merged, preview) = merge(&Settings::default(), &incoming, None).unwrap();
+        assert_eq!(merged.notch_font_size, 24.0);
+        assert_eq!(preview.ignored, vec!["sparkles".to_string()]);
+        assert_eq!(preview.name.as_deref(), Some("Studio"));
+    }
+
+    #[test]
+    fn invalid_values_reject_the_import() {
+        let incoming = file(json!({
+            "notch_font_size": 500.0,
+            "floating_theme": "light",
+        }));
+        let error = merge(&Settings::default(), &incoming, None).unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidSetting);
+    }
+
+    #[test]
+    fn files_from_a_newer_version_are_rejected() {
+        let incoming = file(json!({
+            "version": SETTINGS_VERSION + 1,
+            "notch_font_size": 24.0,
+        }));
+        let error = merge(&Settings::default(), &incoming, None).unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidData);
+    }
+
+    #[test]
+    fn the_preview_shows_both_values() {
+        let current = Settings::default();
+        let incoming = file(json!({
+            "notch_font_size": 24.0,
+            "mirror_mode": current.mirror_mode,
+        }));
+        let (_, preview) = merge(&current, &incoming, None).unwrap();
+
+        // Values equal to the current ones are not changes
+        assert_eq!(preview.changes.len(), 1);
+        let diff = &preview.changes[0];
+        assert_eq!(diff.key, "notch_font_size");
+        assert_eq!(diff.label, "Font Size");
+        assert_eq!(diff.group, "notch");
+        assert_eq!(diff.current, json!(current.notch_font_size));
+        assert_eq!(diff.incoming, json!(24.0));
+    }
+}
@@ -0,0 +1,150 @@
+//! Global keyboard shortcuts.
+//!
+//! Toggle and pause are fixed and always registered. The run list's next
+//! and previous are fixed too, but only registered while a list runs. The
+//! hold-to-scroll key comes from the
+//! settings: while hold mode is on, pressing it emits `prompter-hold-start`
+//! and releasing it `prompter-hold-stop`, and it is re-registered whenever
+//! those settings change.
+
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::models::Settings;
+use crate::state::AppState;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+pub const TOGGLE: &str = "CmdOrCtrl+Shift+W";
+pub const PAUSE: &str = "CmdOrCtrl+Shift+P";
+pub const NEXT: &str = "CmdOrCtrl+Alt+Shift+Right";
+pub const PREVIOUS: &str = "CmdOrCtrl+Alt+Shift+Left";
+
+/// The hold key currently registered, if hold mode is on.
+static HOLD: Mutex<Option<Shortcut>> = Mutex::new(None);
+
+pub fn setup_global_shortcuts(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    // Ctrl+Shift+W — toggle prompter
+    let toggle_shortcut: Shortcut = TOGGLE.parse()?;
+    // Ctrl+Shift+P — pause/resume
+    let pause_shortcut: Shortcut = PAUSE.parse()?;
+
+    let app_handle = app.clone();
+    app.global_shortcut().on_shortcuts(
+        [toggle_shortcut, pause_shortcut],
+        move |_app, shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                if shortcut == &toggle_shortcut {
+                    app_handle.emit("global-toggle-prompter", ()).ok();
+                } else if shortcut == &pause_shortcut {
+                    app_handle.emit("global-pause-prompter", ()).ok();
+                }
+            }
+        },
+    )?;
+
+    restore_hold(app);
+    Ok(())
+}
+
+/// Register next and previous for the running run list; already registered
+/// ones are left as they are. Each key is registered on its own, so one
+/// held by another program still leaves the other, and toggle and pause.
+pub fn register_run_list(app: &AppHandle) -> AppResult<()> {
+    for (key, delta) in [(NEXT, 1), (PREVIOUS, -1)] {
+        let shortcut: Shortcut = key
+            .parse()
+            .map_err(|_| AppError::new(ErrorCode::Internal, format!("{} is not a valid key", key)))?;
+        if app.global_shortcut().is_registered(shortcut) {
+            continue;
+        }
+        app.global_shortcut()
+            .on_shortcut(shortcut, move |app, _, event| {
+                if event.state == ShortcutState::Pressed {
+                    if let Some(state) = app.try_state::<AppState>() {
+                        crate::commands::run_lists::step(app, &state, delta).ok();
+                    }
+                }
+            })
+            .map_err(|e| {
+                AppError::new(ErrorCode::Internal, format!("{} is already in use", key))
+                    .with_details(e.to_string())
+            })?;
+    }
+    Ok(())
+}
+
+/// Free next and previous once no run list is running.
+pub fn unregister_run_list(app: &AppHandle) {
+    for key in [NEXT, PREVIOUS] {
+        if let Ok(shortcut) = key.parse::<Shortcut>() {
+            app.global_shortcut().unregister(shortcut).ok();
+        }
+    }
+}
+
+/// Register the hold key from the stored settings, once the library is open.
+pub fn restore_hold(app: &AppHandle) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    let settings = match state.db.conn.lock() {
+        Ok(conn) => crate::settings::load(&conn),
+        Err(e) => Err(e.into()),
+    };
+    if let Ok(settings) = settings {
+        // A key taken by another program stays off until the setting is changed
+        apply_hold(app, &settings).ok();
+    }
+}
+
+/// Register the hold key `settings` ask for, replacing any previous one.
+/// Does nothing when the key and mode are unchanged.
+pub fn apply_hold(app: &AppHandle, settings: &Settings) -> AppResult<()> {
+    let wanted = if settings.hold_to_scroll {
+        Some(parse_hold(&settings.hold_shortcut)?)
+    } else {
+        None
+    };
+
+    let mut current = HOLD.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if *current == wanted {
+        return Ok(());
+    }
+    if let Some(previous) = current.take() {
+        app.global_shortcut().unregister(previous).ok();
+    }
+    if let Some(shortcut) = wanted {
+        app.global_shortcut()
+            .on_shortcut(shortcut, |app, _, event| {
+                let name = match event.state {
+                    ShortcutState::Pressed => "prompter-hold-start",
+                    ShortcutState::Released => "prompter-hold-stop",
+                };
+                app.emit(name, ()).ok();
+            })
+            .map_err(|e| {
+                AppError::invalid_setting(
+                    "hold_shortcut",
+                    format!("{} is already in use", settings.hold_shortcut),
+                )
+                .with_details(e.to_string())
+            })?;
+        *current = Some(shortcut);
+    }
+    Ok(())
+}
+
+/// Parse a hold key such as `Ctrl+Shift+Space`, rejecting the fixed shortcuts.
+pub fn parse_hold(value: &str) -> AppResult<Shortcut> {
+    let shortcut: Shortcut = value
+        .parse()
+        .map_err(|_| AppError::invalid_setting("hold_shortcut", format!("{} is not a valid key", value)))?;
+    let fixed = [TOGGLE, PAUSE, NEXT, PREVIOUS].iter().filter_map(|s| s.parse::<Shortcut>().ok());
+    if fixed.into_iter().any(|s| s == shortcut) {
+        return Err(AppError::invalid_setting(
+            "hold_shortcut",
+            format!("{} is already used by Whispr", value),
+        ));
+    }
+    Ok(shortcut)
+}
@@ -1,13 +1,32 @@
+use crate::cues::CueDispatcher;
 use crate::db::Database;
+use crate::models::PrompterStatus;
+use crate::run_lists::ActiveRun;
+use crate::sessions::ActiveSession;
+use crate::sync::folder::FolderSync;
+use crate::sync::webdav::WebDavSync;
+use std::sync::Mutex;
 
 pub struct AppState {
     pub db: Database,
+    pub folder_sync: FolderSync,
+    pub webdav_sync: WebDavSync,
+    pub prompter: Mutex<PrompterStatus>,
+    pub session: Mutex<Option<ActiveSession>>,
+    pub cues: CueDispatcher,
+    pub run: Mutex<Option<ActiveRun>>,
 }
 
 impl AppState {
-    pub fn new() -> Self {
+    pub fn new(db: Database) -> Self {
         Self {
-            db: Database::new().expect("Failed to initialize database"),
+            db,
+            folder_sync: FolderSync::default(),
+            webdav_sync: WebDavSync::default(),
+            prompter: Mutex::new(PrompterStatus::default()),
+            session: Mutex::new(None),
+            cues: CueDispatcher::default(),
+            run: Mutex::new(None),
         }
     }
 }
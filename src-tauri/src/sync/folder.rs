@@ -0,0 +1,835 @@
+//! Two-way mirror of the script library to `.md` files in a folder.
+//!
+//! Each script becomes one Markdown file with a front-matter header carrying
+//! its id, title and timestamps. `folder_sync_state` remembers what both sides
+//! looked like after the last pass, so a pass can tell which side changed.
+
+use super::{fingerprint, insert_script, load_scripts};
+use crate::models::Script;
+use crate::state::AppState;
+use crate::vault::Vault;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use rusqlite::{Connection, Transaction};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncReport {
+    pub exported: u32,
+    pub imported: u32,
+    pub deleted_scripts: u32,
+    pub deleted_files: u32,
+    /// Titles of scripts that changed on both sides.
+    pub conflicts: Vec<String>,
+    /// Files that couldn't be read, such as while another program has them
+    /// locked; their scripts are left alone until a later pass.
+    pub unreadable: Vec<String>,
+}
+
+impl SyncReport {
+    /// Whether the pass changed the `scripts` table.
+    pub fn changed_library(&self) -> bool {
+        self.imported > 0 || self.deleted_scripts > 0 || !self.conflicts.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FolderSyncStatus {
+    pub folder: Option<String>,
+    pub last_synced_at: Option<String>,
+    pub last_error: Option<String>,
+    pub last_report: Option<SyncReport>,
+}
+
+struct Running {
+    // Dropping the watcher and sender ends the worker thread
+    _watcher: RecommendedWatcher,
+    tx: Sender<()>,
+}
+
+/// Watches the sync folder and reconciles it on a background thread.
+#[derive(Default)]
+pub struct FolderSync {
+    running: Mutex<Option<Running>>,
+    status: Mutex<FolderSyncStatus>,
+}
+
+impl FolderSync {
+    pub fn start(&self, app: &AppHandle, dir: PathBuf) -> Result<(), String> {
+        self.stop();
+
+        let (tx, rx) = mpsc::channel::<()>();
+        let watch_tx = tx.clone();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            if res.is_ok() {
+                watch_tx.send(()).ok();
+            }
+        })
+        .map_err(|e| e.to_string())?;
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .map_err(|e| e.to_string())?;
+
+        let app_handle = app.clone();
+        std::thread::spawn(move || {
+            while rx.recv().is_ok() {
+                // Let a burst of editor writes settle before reconciling
+                std::thread::sleep(Duration::from_millis(300));
+                while rx.try_recv().is_ok() {}
+                sync_now(&app_handle).ok();
+            }
+        });
+
+        // Initial pass
+        tx.send(()).ok();
+
+        *self.running.lock().unwrap() = Some(Running {
+            _watcher: watcher,
+            tx,
+        });
+        let mut status = self.status.lock().unwrap();
+        *status = FolderSyncStatus {
+            folder: Some(dir.to_string_lossy().into_owned()),
+            ..Default::default()
+        };
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        *self.running.lock().unwrap() = None;
+        *self.status.lock().unwrap() = FolderSyncStatus::default();
+    }
+
+    /// Queue a pass after the library changed inside the app.
+    pub fn request_sync(&self) {
+        if let Some(running) = self.running.lock().unwrap().as_ref() {
+            running.tx.send(()).ok();
+        }
+    }
+
+    pub fn status(&self) -> FolderSyncStatus {
+        self.status.lock().unwrap().clone()
+    }
+}
+
+/// Start watching the configured folder, if any.
+pub fn resume(app: &AppHandle) {
+    let Some(folder) = super::SyncConfig::load().folder else {
+        return;
+    };
+    let state = app.state::<AppState>();
+    if let Err(e) = state.folder_sync.start(app, PathBuf::from(&folder)) {
+        let mut status = state.folder_sync.status.lock().unwrap();
+        status.folder = Some(folder);
+        status.last_error = Some(e);
+    }
+}
+
+/// Run one reconcile pass against the configured folder.
+pub fn sync_now(app: &AppHandle) -> Result<SyncReport, String> {
+    let state = app.state::<AppState>();
+    state.db.ensure_writable()?;
+    let folder = state
+        .folder_sync
+        .status()
+        .folder
+        .ok_or("Folder sync is not enabled")?;
+
+    let result = {
+        let mut conn = state.db.conn.lock().map_err(|e| e.to_string())?;
+        reconcile(&mut conn, &state.db.vault, Path::new(&folder))
+    };
+
+    {
+        let mut status = state.folder_sync.status.lock().unwrap();
+        status.last_synced_at = Some(chrono::Utc::now().to_rfc3339());
+        match &result {
+            Ok(report) => {
+                status.last_error = None;
+                status.last_report = Some(report.clone());
+            }
+            Err(e) => status.last_error = Some(e.clone()),
+        }
+    }
+
+    if let Ok(report) = &result {
+        if report.changed_library() {
+            app.emit("library-changed", ()).ok();
+            crate::tray::refresh(app);
+        }
+    }
+    result
+}
+
+struct SyncState {
+    file_name: String,
+    fingerprint: String,
+    synced_updated_at: String,
+}
+
+/// A script file found in the sync folder.
+struct MirrorFile {
+    file_name: String,
+    id: Option<String>,
+    title: String,
+    content: String,
+    created_at: Option<String>,
+}
+
+impl MirrorFile {
+    fn fingerprint(&self) -> String {
+        fingerprint(&self.title, &self.content)
+    }
+}
+
+/// Bring the folder and the `scripts` table back in line with each other.
+pub fn reconcile(conn: &mut Connection, vault: &Vault, dir: &Path) -> Result<SyncReport, String> {
+    super::ensure_unencrypted(vault)?;
+    if !dir.is_dir() {
+        return Err(format!("Sync folder {} is not available", dir.display()));
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut staged = Staged::new(dir);
+    let scripts = load_scripts(&tx, vault)?;
+    let states = load_states(&tx)?;
+    let Scan {
+        files,
+        unclaimed,
+        unreadable,
+    } = scan(dir)?;
+
+    // An empty folder after a previous sync is far more likely an unmounted
+    // drive than the user deleting every file
+    if files.is_empty() && unclaimed.is_empty() && unreadable.is_empty() && !states.is_empty() {
+        return Err(format!(
+            "Sync folder {} is empty; not deleting {} scripts",
+            dir.display(),
+            states.len()
+        ));
+    }
+
+    let mut report = SyncReport {
+        unreadable: unreadable.clone(),
+        ..SyncReport::default()
+    };
+    let mut used_names: HashSet<String> = files
+        .values()
+        .chain(unclaimed.iter())
+        .map(|f| f.file_name.clone())
+        .chain(unreadable.iter().cloned())
+        .collect();
+    let now = chrono::Utc::now().to_rfc3339();
+
+    // New files written outside the app
+    for file in &unclaimed {
+        let script = Script {
+            id: uuid::Uuid::new_v4().to_string(),
+            title: file.title.clone(),
+            content: file.content.clone(),
+            created_at: file.created_at.clone().unwrap_or_else(|| now.clone()),
+            updated_at: now.clone(),
+            word_count: None,
+            estimated_seconds: None,
+        };
+        insert_script(&tx, vault, &script)?;
+        export(&tx, &mut staged, &file.file_name, &script)?;
+        report.imported += 1;
+    }
+
+    let ids: BTreeSet<&String> = scripts.keys().chain(files.keys()).chain(states.keys()).collect();
+    for id in ids {
+        let state = states.get(id);
+        match (scripts.get(id), files.get(id)) {
+            (Some(script), Some(file)) => {
+                if script.title == file.title && script.content == file.content {
+                    let stale = state.is_none_or(|s| {
+                        s.file_name != file.file_name || s.synced_updated_at != script.updated_at
+                    });
+                    if stale {
+                        save_state(&tx, id, &file.file_name, &file.fingerprint(), &script.updated_at)?;
+                    }
+                    continue;
+                }
+                let db_changed = state.is_none_or(|s| s.synced_updated_at != script.updated_at);
+                let file_changed = state.is_none_or(|s| s.fingerprint != file.fingerprint());
+
+                match (db_changed, file_changed) {
+                    (false, false) => {}
+                    (true, false) => {
+                        export(&tx, &mut staged, &file.file_name, script)?;
+                        report.exported += 1;
+                    }
+                    (false, true) => {
+                        tx.execute(
+                            "UPDATE scripts SET title = ?1, content = ?2, updated_at = ?3 WHERE id = ?4",
+                            rusqlite::params![file.title, vault.seal(&file.content)?, now, id],
+                        )
+                        .map_err(|e| e.to_string())?;
+                        save_state(&tx, id, &file.file_name, &file.fingerprint(), &now)?;
+                        report.imported += 1;
+                    }
+                    (true, true) => {
+                        // Keep the app's version under the original id and
+                        // the file's version as a separate conflict copy
+                        let copy = Script {
+                            id: uuid::Uuid::new_v4().to_string(),
+                            title: format!(
+                                "{} (conflict copy {})",
+                                file.title,
+                                chrono::Local::now().format("%Y-%m-%d %H:%M")
+                            ),
+                            content: file.content.clone(),
+                            created_at: now.clone(),
+                            updated_at: now.clone(),
+                            word_count: None,
+                            estimated_seconds: None,
+                        };
+                        insert_script(&tx, vault, &copy)?;
+                        let copy_name = unique_file_name(&copy.title, &mut used_names);
+                        export(&tx, &mut staged, &copy_name, &copy)?;
+                        export(&tx, &mut staged, &file.file_name, script)?;
+                        report.conflicts.push(script.title.clone());
+                    }
+                }
+            }
+            // Its file is there but couldn't be read: neither deleted nor stale
+            (Some(_), None) if state.is_some_and(|s| unreadable.contains(&s.file_name)) => {}
+            (Some(script), None) => {
+                let deleted_externally = state.is_some_and(|s| s.synced_updated_at == script.updated_at);
+                if deleted_externally {
+                    tx.execute("DELETE FROM scripts WHERE id = ?1", rusqlite::params![id])
+                        .map_err(|e| e.to_string())?;
+                    delete_state(&tx, id)?;
+                    report.deleted_scripts += 1;
+                } else {
+                    let name = state
+                        .map(|s| s.file_name.clone())
+                        .filter(|name| !used_names.contains(name))
+                        .unwrap_or_else(|| unique_file_name(&script.title, &mut used_names));
+                    used_names.insert(name.clone());
+                    export(&tx, &mut staged, &name, script)?;
+                    report.exported += 1;
+                }
+            }
+            (None, Some(file)) => {
+                let deleted_in_app = state.is_some_and(|s| s.fingerprint == file.fingerprint());
+                if deleted_in_app {
+                    staged.remove(&file.file_name);
+                    delete_state(&tx, id)?;
+                    report.deleted_files += 1;
+                } else {
+                    // Edited after the app deleted it, or written by another machine
+                    let script = Script {
+                        id: id.clone(),
+                        title: file.title.clone(),
+                        content: file.content.clone(),
+                        created_at: file.created_at.clone().unwrap_or_else(|| now.clone()),
+                        updated_at: now.clone(),
+                        word_count: None,
+                        estimated_seconds: None,
+                    };
+                    insert_script(&tx, vault, &script)?;
+                    save_state(&tx, id, &file.file_name, &file.fingerprint(), &now)?;
+                    report.imported += 1;
+                }
+            }
+            (None, None) => delete_state(&tx, id)?,
+        }
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+    staged.apply()?;
+    Ok(report)
+}
+
+/// File changes made by a pass, held back until its transaction commits so
+/// the folder and the database can't drift apart. New contents are written
+/// to hidden temp files up front and renamed into place by `apply`; dropping
+/// without applying removes them again.
+struct Staged<'a> {
+    dir: &'a Path,
+    writes: Vec<(PathBuf, PathBuf)>,
+    removals: Vec<PathBuf>,
+}
+
+impl<'a> Staged<'a> {
+    fn new(dir: &'a Path) -> Self {
+        Self {
+            dir,
+            writes: Vec::new(),
+            removals: Vec::new(),
+        }
+    }
+
+    fn write(&mut self, file_name: &str, text: &str) -> Result<(), String> {
+        let tmp = self.dir.join(format!(".{}.tmp", file_name));
+        std::fs::write(&tmp, text).map_err(|e| e.to_string())?;
+        self.writes.push((tmp, self.dir.join(file_name)));
+        Ok(())
+    }
+
+    fn remove(&mut self, file_name: &str) {
+        self.removals.push(self.dir.join(file_name));
+    }
+
+    fn apply(mut self) -> Result<(), String> {
+        // Renaming means a watcher never sees half a file
+        for (tmp, target) in std::mem::take(&mut self.writes) {
+            std::fs::rename(&tmp, &target).map_err(|e| e.to_string())?;
+        }
+        for path in std::mem::take(&mut self.removals) {
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.to_string()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Staged<'_> {
+    fn drop(&mut self) {
+        for (tmp, _) in &self.writes {
+            std::fs::remove_file(tmp).ok();
+        }
+    }
+}
+
+fn load_states(conn: &Connection) -> Result<HashMap<String, SyncState>, String> {
+    let mut stmt = conn
+        .prepare("SELECT script_id, file_name, fingerprint, synced_updated_at FROM folder_sync_state")
+        .map_err(|e| e.to_string())?;
+    let states = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                SyncState {
+                    file_name: row.get(1)?,
+                    fingerprint: row.get(2)?,
+                    synced_updated_at: row.get(3)?,
+                },
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(states)
+}
+
+fn save_state(tx: &Transaction, id: &str, file_name: &str, fingerprint: &str, synced_updated_at: &str) -> Result<(), String> {
+    tx.execute(
+        "INSERT OR REPLACE INTO folder_sync_state (script_id, file_name, fingerprint, synced_updated_at) VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![id, file_name, fingerprint, synced_updated_at],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn delete_state(tx: &Transaction, id: &str) -> Result<(), String> {
+    tx.execute("DELETE FROM folder_sync_state WHERE script_id = ?1", rusqlite::params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Stage a script's file and record the synced state.
+fn export(tx: &Transaction, staged: &mut Staged, file_name: &str, script: &Script) -> Result<(), String> {
+    let text = format!(
+        "---\nid: {}\ntitle: {}\ncreated_at: {}\nupdated_at: {}\n---\n{}",
+        script.id,
+        serde_json::to_string(&script.title).map_err(|e| e.to_string())?,
+        script.created_at,
+        script.updated_at,
+        script.content,
+    );
+    staged.write(file_name, &text)?;
+
+    save_state(
+        tx,
+        &script.id,
+        file_name,
+        &fingerprint(&script.title, &script.content),
+        &script.updated_at,
+    )
+}
+
+/// The `.md` files of the sync folder, as read by `scan`.
+struct Scan {
+    /// Files carrying a known id, by id
+    files: HashMap<String, MirrorFile>,
+    /// Files to import as new scripts
+    unclaimed: Vec<MirrorFile>,
+    /// Names of files that couldn't be read
+    unreadable: Vec<String>,
+}
+
+/// Read every `.md` file in the folder.
+fn scan(dir: &Path) -> Result<Scan, String> {
+    let mut files = HashMap::new();
+    let mut unclaimed = Vec::new();
+    let mut unreadable = Vec::new();
+
+    for entry in std::fs::read_dir(dir).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if file_name.starts_with('.') || path.extension().and_then(|e| e.to_str()) != Some("md") {
+            continue;
+        }
+        let Ok(bytes) = std::fs::read(&path) else {
+            unreadable.push(file_name.to_string());
+            continue;
+        };
+        let text = String::from_utf8_lossy(&bytes);
+        let file = parse_file(file_name, &text);
+
+        match &file.id {
+            // A copied file keeps the original's id; treat the copy as new
+            Some(id) if !files.contains_key(id) => {
+                files.insert(id.clone(), file);
+            }
+            _ => unclaimed.push(file),
+        }
+    }
+
+    unreadable.sort();
+    Ok(Scan {
+        files,
+        unclaimed,
+        unreadable,
+    })
+}
+
+fn parse_file(file_name: &str, text: &str) -> MirrorFile {
+    let (front, body) = split_front_matter(text);
+    let stem = file_name.strip_suffix(".md").unwrap_or(file_name);
+
+    MirrorFile {
+        file_name: file_name.to_string(),
+        id: front.get("id").filter(|id| !id.is_empty()).cloned(),
+        title: front
+            .get("title")
+            .filter(|t| !t.is_empty())
+            .cloned()
+            .unwrap_or_else(|| stem.to_string()),
+        content: body.to_string(),
+        created_at: front.get("created_at").cloned(),
+    }
+}
+
+/// Split a `---` fenced header from the body. Values may be plain or
+/// JSON-quoted strings.
+fn split_front_matter(text: &str) -> (HashMap<String, String>, &str) {
+    let mut front = HashMap::new();
+    let mut lines = text.split_inclusive('\n');
+
+    match lines.next() {
+        Some(first) if first.trim_end() == "---" => {}
+        _ => return (front, text),
+    }
+
+    let mut offset = text.split_inclusive('\n').next().map_or(0, str::len);
+    for line in lines {
+        offset += line.len();
+        let line = line.trim_end();
+        if line == "---" {
+            return (front, &text[offset..]);
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            let value = value.trim();
+            let value = if value.starts_with('"') {
+                serde_json::from_str(value).unwrap_or_else(|_| value.to_string())
+            } else {
+                value.to_string()
+            };
+            front.insert(key.trim().to_string(), value);
+        }
+    }
+
+    // No closing fence — not front-matter after all
+    (HashMap::new(), text)
+}
+
+fn unique_file_name(title: &str, used: &mut HashSet<String>) -> String {
+    let mut slug = String::new();
+    for c in title.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug: String = slug.trim_matches('-').chars().take(60).collect();
+    let slug = if slug.is_empty() { "untitled".to_string() } else { slug };
+
+    let mut name = format!("{}.md", slug);
+    let mut n = 2;
+    while used.contains(&name) {
+        name = format!("{}-{}.md", slug, n);
+        n += 1;
+    }
+    used.insert(name.clone());
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A library and an empty sync folder, removed again on drop.
+    struct Mirror {
+        conn: Connection,
+        dir: PathBuf,
+    }
+
+    impl Mirror {
+        fn new() -> Self {
+            let conn = Connection::open_in_memory().unwrap();
+            crate::db::apply_migrations(&conn).unwrap();
+            let dir = std::env::temp_dir().join(format!("whispr-folder-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir(&dir).unwrap();
+            Self { conn, dir }
+        }
+
+        fn sync(&mut self) -> SyncReport {
+            reconcile(&mut self.conn, &Vault::disabled(), &self.dir).unwrap()
+        }
+
+        fn add(&self, id: &str, content: &str) {
+            insert_script(
+                &self.conn,
+                &Vault::disabled(),
+                &Script {
+                    id: id.into(),
+                    title: id.into(),
+                    content: content.into(),
+                    created_at: "2026-01-01T00:00:00Z".into(),
+                    updated_at: "2026-01-01T00:00:00Z".into(),
+                    word_count: None,
+                    estimated_seconds: None,
+                },
+            )
+            .unwrap();
+        }
+
+        fn edit(&self, id: &str, content: &str) {
+            self.conn
+                .execute(
+                    "UPDATE scripts SET content = ?1, updated_at = ?2 WHERE id = ?3",
+                    rusqlite::params![content, chrono::Utc::now().to_rfc3339(), id],
+                )
+                .unwrap();
+        }
+
+        fn delete(&self, id: &str) {
+            self.conn
+                .execute("DELETE FROM scripts WHERE id = ?1", rusqlite::params![id])
+                .unwrap();
+        }
+
+        fn content(&self, id: &str) -> Option<String> {
+            self.conn
+                .query_row("SELECT content FROM scripts WHERE id = ?1", rusqlite::params![id], |row| row.get(0))
+                .ok()
+        }
+
+        fn titles(&self) -> Vec<String> {
+            let mut stmt = self.conn.prepare("SELECT title FROM scripts ORDER BY title").unwrap();
+            stmt.query_map([], |row| row.get(0))
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap()
+        }
+
+        fn tombstoned(&self, id: &str) -> bool {
+            self.conn
+                .query_row("SELECT COUNT(*) FROM tombstones WHERE script_id = ?1", rusqlite::params![id], |row| {
+                    row.get::<_, i64>(0)
+                })
+                .unwrap()
+                > 0
+        }
+
+        fn read(&self, file_name: &str) -> Option<MirrorFile> {
+            let text = std::fs::read_to_string(self.dir.join(file_name)).ok()?;
+            Some(parse_file(file_name, &text))
+        }
+
+        /// Rewrite a mirrored file's body, keeping its front matter.
+        fn write_body(&self, file_name: &str, body: &str) {
+            let text = std::fs::read_to_string(self.dir.join(file_name)).unwrap();
+            let (_, old_body) = split_front_matter(&text);
+            let front = &text[..text.len() - old_body.len()];
+            std::fs::write(self.dir.join(file_name), format!("{}{}", front, body)).unwrap();
+        }
+
+        fn file_names(&self) -> Vec<String> {
+            let mut names: Vec<String> = std::fs::read_dir(&self.dir)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+                .collect();
+            names.sort();
+            names
+        }
+    }
+
+    impl Drop for Mirror {
+        fn drop(&mut self) {
+            std::fs::remove_dir_all(&self.dir).ok();
+        }
+    }
+
+    #[test]
+    fn new_files_are_imported_and_claimed() {
+        let mut mirror = Mirror::new();
+        std::fs::write(mirror.dir.join("Opening.md"), "Good evening.").unwrap();
+
+        let report = mirror.sync();
+        assert_eq!(report.imported, 1);
+        assert_eq!(mirror.titles(), ["Opening"]);
+        // The file now carries the new script's id, so the next pass is quiet
+        let file = mirror.read("Opening.md").unwrap();
+        assert_eq!(mirror.content(file.id.as_deref().unwrap()).unwrap(), "Good evening.");
+        assert_eq!(mirror.sync().imported, 0);
+    }
+
+    #[test]
+    fn edits_travel_both_ways() {
+        let mut mirror = Mirror::new();
+        mirror.add("intro", "Hello");
+        mirror.add("outro", "Goodbye");
+        assert_eq!(mirror.sync().exported, 2);
+
+        mirror.write_body("intro.md", "Hello, everyone");
+        let report = mirror.sync();
+        assert_eq!((report.imported, report.exported), (1, 0));
+        assert_eq!(mirror.content("intro").unwrap(), "Hello, everyone");
+
+        mirror.edit("outro", "See you next week");
+        let report = mirror.sync();
+        assert_eq!((report.imported, report.exported), (0, 1));
+        assert_eq!(mirror.read("outro.md").unwrap().content, "See you next week");
+    }
+
+    #[test]
+    fn edits_on_both_sides_keep_a_conflict_copy() {
+        let mut mirror = Mirror::new();
+        mirror.add("intro", "Hello");
+        mirror.sync();
+
+        mirror.edit("intro", "Hello from the app");
+        mirror.write_body("intro.md", "Hello from the folder");
+        let report = mirror.sync();
+        assert_eq!(report.conflicts, ["intro"]);
+
+        // The app's version keeps the id and the file; the folder's becomes a copy
+        assert_eq!(mirror.content("intro").unwrap(), "Hello from the app");
+        assert_eq!(mirror.read("intro.md").unwrap().content, "Hello from the app");
+        let titles = mirror.titles();
+        assert_eq!(titles.len(), 2);
+        assert!(titles[1].starts_with("intro (conflict copy "));
+        let copy = mirror.file_names().into_iter().find(|name| name != "intro.md").unwrap();
+        assert_eq!(mirror.read(&copy).unwrap().content, "Hello from the folder");
+        assert!(mirror.sync().conflicts.is_empty());
+    }
+
+    #[test]
+    fn an_unreadable_file_keeps_its_script() {
+        let mut mirror = Mirror::new();
+        mirror.add("intro", "Hello");
+        mirror.add("outro", "Goodbye");
+        mirror.sync();
+
+        // A directory in its place reads as an error, like a locked file
+        let saved = std::fs::read(mirror.dir.join("intro.md")).unwrap();
+        std::fs::remove_file(mirror.dir.join("intro.md")).unwrap();
+        std::fs::create_dir(mirror.dir.join("intro.md")).unwrap();
+        let report = mirror.sync();
+        assert_eq!(report.unreadable, ["intro.md"]);
+        assert_eq!((report.deleted_scripts, report.exported), (0, 0));
+        assert_eq!(mirror.content("intro").unwrap(), "Hello");
+
+        std::fs::remove_dir(mirror.dir.join("intro.md")).unwrap();
+        std::fs::write(mirror.dir.join("intro.md"), saved).unwrap();
+        let report = mirror.sync();
+        assert!(report.unreadable.is_empty());
+        assert_eq!(report.deleted_scripts, 0);
+        assert_eq!(mirror.titles(), ["intro", "outro"]);
+    }
+
+    #[test]
+    fn deletes_travel_both_ways() {
+        let mut mirror = Mirror::new();
+        mirror.add("intro", "Hello");
+        mirror.add("segment", "News");
+        mirror.add("outro", "Goodbye");
+        mirror.sync();
+
+        std::fs::remove_file(mirror.dir.join("intro.md")).unwrap();
+        assert_eq!(mirror.sync().deleted_scripts, 1);
+        assert_eq!(mirror.content("intro"), None);
+        assert!(mirror.tombstoned("intro"));
+
+        mirror.delete("outro");
+        assert_eq!(mirror.sync().deleted_files, 1);
+        assert_eq!(mirror.file_names(), ["segment.md"]);
+    }
+
+    #[test]
+    fn a_file_edited_after_its_delete_brings_the_script_back() {
+        let mut mirror = Mirror::new();
+        mirror.add("intro", "Hello");
+        mirror.add("outro", "Goodbye");
+        mirror.sync();
+
+        mirror.delete("intro");
+        assert!(mirror.tombstoned("intro"));
+        mirror.write_body("intro.md", "Hello again");
+        assert_eq!(mirror.sync().imported, 1);
+        assert_eq!(mirror.content("intro").unwrap(), "Hello again");
+        assert!(!mirror.tombstoned("intro"));
+    }
+
+    #[test]
+    fn an_emptied_folder_deletes_nothing() {
+        let mut mirror = Mirror::new();
+        mirror.add("intro", "Hello");
+        mirror.sync();
+
+        std::fs::remove_file(mirror.dir.join("intro.md")).unwrap();
+        assert!(reconcile(&mut mirror.conn, &Vault::disabled(), &mirror.dir).is_err());
+        assert_eq!(mirror.content("intro").unwrap(), "Hello");
+    }
+
+    #[test]
+    fn an_encrypted_library_is_not_mirrored() {
+        let mut mirror = Mirror::new();
+        mirror.add("intro", "Hello");
+        let vault = Vault::disabled();
+        vault.enable(&mut mirror.conn, "correct horse").unwrap();
+
+        assert!(reconcile(&mut mirror.conn, &vault, &mirror.dir).is_err());
+        assert!(mirror.file_names().is_empty());
+    }
+
+    #[test]
+    fn staged_files_wait_for_apply() {
+        let mirror = Mirror::new();
+        let mut staged = Staged::new(&mirror.dir);
+        staged.write("intro.md", "Hello").unwrap();
+        assert!(mirror.read("intro.md").is_none());
+        drop(staged);
+        assert!(mirror.file_names().is_empty());
+
+        let mut staged = Staged::new(&mirror.dir);
+        staged.write("intro.md", "Hello").unwrap();
+        staged.apply().unwrap();
+        assert_eq!(mirror.file_names(), ["intro.md"]);
+    }
+}
@@ -0,0 +1,104 @@
+pub mod folder;
+pub mod webdav;
+
+use crate::models::Script;
+use crate::vault::Vault;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+/// Machine-specific sync configuration. Kept out of `Settings` so that
+/// folder paths and server credentials never travel with settings to
+/// another computer.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncConfig {
+    pub folder: Option<String>,
+    pub webdav: Option<webdav::WebDavConfig>,
+}
+
+impl SyncConfig {
+    fn path() -> PathBuf {
+        crate::paths::data_file("sync.json")
+    }
+
+    /// Missing or unreadable config means sync is off.
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let data = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        crate::files::write_atomic(&Self::path(), data.as_bytes()).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+/// Start every configured sync engine. Called once the database is open.
+pub fn resume(app: &AppHandle) {
+    folder::resume(app);
+    webdav::resume(app);
+}
+
+/// Synced copies are plain text, so sync stays off while the library is
+/// encrypted rather than leave a readable copy of every script behind.
+pub fn ensure_unencrypted(vault: &Vault) -> Result<(), String> {
+    if vault.status().enabled {
+        return Err("Sync is off while the library is encrypted, since synced copies aren't encrypted".into());
+    }
+    Ok(())
+}
+
+/// Stable content fingerprint (FNV-1a, 64-bit) used to tell whether a
+/// script changed since it was last synced.
+pub fn fingerprint(title: &str, content: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in title.bytes().chain([0]).chain(content.bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+pub fn load_scripts(conn: &Connection, vault: &Vault) -> Result<HashMap<String, Script>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, title, content, created_at, updated_at FROM scripts")
+        .map_err(|e| e.to_string())?;
+    let scripts = stmt
+        .query_map([], |row| {
+            Ok(Script {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                content: row.get(2)?,
+                created_at: row.get(3)?,
+                updated_at: row.get(4)?,
+                word_count: None,
+                estimated_seconds: None,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    scripts
+        .into_iter()
+        .map(|script| {
+            let script = vault.open_script(script)?;
+            Ok((script.id.clone(), script))
+        })
+        .collect()
+}
+
+pub fn insert_script(conn: &Connection, vault: &Vault, script: &Script) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO scripts (id, title, content, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![script.id, script.title, vault.seal(&script.content)?, script.created_at, script.updated_at],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
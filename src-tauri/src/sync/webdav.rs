@@ -0,0 +1,1167 @@
+//! Push/pull sync of the script library against a WebDAV collection.
+//!
+//! Each script is stored remotely as `<id>.json`. Deleted scripts stay on the
+//! server as tombstones (`deleted: true`) so other machines learn about the
+//! delete. `webdav_sync_state` records the local `updated_at` and remote
+//! version (ETag) seen at the last sync, which is how each side's changes
+//! are detected.
+
+use super::{insert_script, load_scripts};
+use crate::models::Script;
+use crate::state::AppState;
+use crate::vault::Vault;
+use base64::Engine;
+use rusqlite::{Connection, Transaction};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Keyring service the server password is stored under.
+const KEYRING_SERVICE: &str = "Whispr WebDAV";
+
+/// `synced_updated_at` marker for scripts whose tombstone is in sync.
+const DELETED: &str = "deleted";
+/// `synced_updated_at` marker for scripts whose remote version has been
+/// merged but whose local version is still to be uploaded.
+const UNSYNCED: &str = "";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebDavConfig {
+    /// Collection URL, e.g. `https://dav.example.com/whispr/`.
+    pub url: String,
+    #[serde(default)]
+    pub username: String,
+    /// Sent by the settings page and kept in the OS keyring, never written
+    /// to sync.json.
+    #[serde(default, skip_serializing)]
+    pub password: String,
+    /// Sync in the background this often. `None` syncs only on request.
+    #[serde(default)]
+    pub interval_minutes: Option<u32>,
+}
+
+impl WebDavConfig {
+    /// The URL must be `https://`, except that a server on this machine
+    /// may use `http://`: anywhere else, Basic auth over plain HTTP would
+    /// send the password readable to the whole network.
+    pub fn validate(&self) -> Result<(), String> {
+        let url = self.url.trim();
+        if url.len() > 8 && url[..8].eq_ignore_ascii_case("https://") {
+            return Ok(());
+        }
+        if !(url.len() > 7 && url[..7].eq_ignore_ascii_case("http://")) {
+            return Err("The WebDAV address must start with https://".into());
+        }
+        let authority = url[7..].split('/').next().unwrap_or("");
+        let host = authority.rsplit('@').next().unwrap_or(authority);
+        let host = match host.strip_prefix('[') {
+            Some(rest) => rest.split(']').next().unwrap_or(rest),
+            None => host.split(':').next().unwrap_or(host),
+        };
+        let local = host.eq_ignore_ascii_case("localhost") || host == "::1" || host.starts_with("127.");
+        if self.username.is_empty() || local {
+            Ok(())
+        } else {
+            Err("Use an https:// address; over http:// the password would be sent unencrypted".into())
+        }
+    }
+
+    fn keyring_entry(&self) -> Result<keyring::Entry, String> {
+        keyring::Entry::new(KEYRING_SERVICE, &format!("{}@{}", self.username, self.url)).map_err(|e| e.to_string())
+    }
+
+    /// Put `password` in the keyring, or remove the stored one if it's empty.
+    pub fn store_password(&self) -> Result<(), String> {
+        if self.password.is_empty() {
+            self.forget_password();
+            return Ok(());
+        }
+        self.keyring_entry()?
+            .set_password(&self.password)
+            .map_err(|e| format!("Couldn't save the WebDAV password: {}", e))
+    }
+
+    /// A copy with the password read back from the keyring. One still in a
+    /// sync.json from an older version is used as it is.
+    pub fn with_password(&self) -> Result<Self, String> {
+        let mut config = self.clone();
+        if config.password.is_empty() {
+            config.password = match self.keyring_entry()?.get_password() {
+                Ok(password) => password,
+                Err(keyring::Error::NoEntry) => String::new(),
+                Err(e) => return Err(format!("Couldn't read the WebDAV password: {}", e)),
+            };
+        }
+        Ok(config)
+    }
+
+    pub fn forget_password(&self) {
+        if let Ok(entry) = self.keyring_entry() {
+            entry.delete_credential().ok();
+        }
+    }
+}
+
+/// A script as stored on the server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteScript {
+    pub id: String,
+    pub title: String,
+    pub content: String,
+    pub created_at: String,
+    pub updated_at: String,
+    #[serde(default)]
+    pub deleted: bool,
+}
+
+impl From<&Script> for RemoteScript {
+    fn from(script: &Script) -> Self {
+        Self {
+            id: script.id.clone(),
+            title: script.title.clone(),
+            content: script.content.clone(),
+            created_at: script.created_at.clone(),
+            updated_at: script.updated_at.clone(),
+            deleted: false,
+        }
+    }
+}
+
+/// Where scripts are pushed to and pulled from. Versions are opaque strings
+/// (ETags) that change whenever the remote file changes.
+pub trait RemoteStore {
+    /// Current version of every script on the server, keyed by id.
+    fn list(&self) -> Result<HashMap<String, String>, String>;
+    fn get(&self, id: &str) -> Result<(RemoteScript, String), String>;
+    /// Upload a script only if the server still has `expected`, or has no
+    /// copy at all when `expected` is `None`. Returns the new version, or
+    /// `None` when the server's copy changed in the meantime.
+    fn put(&self, script: &RemoteScript, expected: Option<&str>) -> Result<Option<String>, String>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    /// Both sides edited: local kept, remote saved as a conflict copy.
+    KeptBoth,
+    /// Edited locally, deleted remotely: local version uploaded again.
+    RestoredLocal,
+    /// Deleted locally, edited remotely: remote version restored.
+    RestoredRemote,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncConflict {
+    pub script_id: String,
+    pub title: String,
+    pub resolution: ConflictResolution,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WebDavReport {
+    pub pushed: u32,
+    pub pulled: u32,
+    pub deleted_local: u32,
+    pub deleted_remote: u32,
+    pub conflicts: Vec<SyncConflict>,
+    /// Uploads the server refused because another device changed the file
+    /// after it was listed; they're merged again on the next pass.
+    pub deferred: u32,
+}
+
+impl WebDavReport {
+    pub fn changed_library(&self) -> bool {
+        self.pulled > 0 || self.deleted_local > 0 || !self.conflicts.is_empty()
+    }
+}
+
+struct SyncState {
+    synced_updated_at: String,
+    remote_version: String,
+}
+
+/// Run one full push/pull pass. The database is locked only to read the
+/// sync state, to apply what was downloaded in one short transaction, and to
+/// record each upload, so a slow server never holds up the rest of the app.
+pub fn sync(db: &Mutex<Connection>, vault: &Vault, remote: &dyn RemoteStore) -> Result<WebDavReport, String> {
+    super::ensure_unencrypted(vault)?;
+    // Only a pass writes sync state, and passes don't overlap, so what's
+    // read here is still current when the changes are applied
+    let states = load_states(&*lock(db)?)?;
+    let versions = remote.list()?;
+    let mut downloads = HashMap::new();
+    for (id, version) in &versions {
+        if states.get(id).is_none_or(|s| s.remote_version != *version) {
+            downloads.insert(id.clone(), remote.get(id)?);
+        }
+    }
+
+    let (mut report, uploads) = {
+        let mut conn = lock(db)?;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let plan = merge(&tx, vault, &versions, downloads)?;
+        tx.commit().map_err(|e| e.to_string())?;
+        plan
+    };
+
+    for upload in uploads {
+        match remote.put(&upload.script, upload.expected.as_deref())? {
+            Some(version) => save_state(&*lock(db)?, &upload.script.id, &upload.synced_updated_at, &version)?,
+            // Left unsynced, so the next pass downloads their version and merges it
+            None => report.deferred += 1,
+        }
+    }
+    Ok(report)
+}
+
+fn lock(db: &Mutex<Connection>) -> Result<std::sync::MutexGuard<'_, Connection>, String> {
+    db.lock().map_err(|e| e.to_string())
+}
+
+/// A script or tombstone to upload once the merge has committed, the
+/// server version it was merged against (`None` for a new file), and the
+/// `synced_updated_at` to record when it's on the server.
+struct Upload {
+    script: RemoteScript,
+    expected: Option<String>,
+    synced_updated_at: String,
+}
+
+impl Upload {
+    fn script(script: &Script, expected: Option<&String>) -> Self {
+        Self {
+            script: RemoteScript::from(script),
+            expected: expected.cloned(),
+            synced_updated_at: script.updated_at.clone(),
+        }
+    }
+}
+
+/// Apply the downloaded remote changes to the library and work out what
+/// needs uploading. `downloads` holds every script whose remote version
+/// differs from the one last synced.
+fn merge(
+    tx: &Transaction,
+    vault: &Vault,
+    versions: &HashMap<String, String>,
+    mut downloads: HashMap<String, (RemoteScript, String)>,
+) -> Result<(WebDavReport, Vec<Upload>), String> {
+    let scripts = load_scripts(tx, vault)?;
+    let tombstones = load_tombstones(tx)?;
+    let states = load_states(tx)?;
+    let mut report = WebDavReport::default();
+    let mut uploads = Vec::new();
+
+    let ids: BTreeSet<&String> = scripts
+        .keys()
+        .chain(tombstones.keys())
+        .chain(versions.keys())
+        .chain(states.keys())
+        .collect();
+
+    for id in ids {
+        let state = states.get(id);
+        let version = versions.get(id);
+        let remote_changed = match (version, state) {
+            (Some(v), Some(s)) => *v != s.remote_version,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+
+        if let Some(script) = scripts.get(id) {
+            let local_changed = state.is_none_or(|s| s.synced_updated_at != script.updated_at);
+
+            if version.is_none() || (local_changed && !remote_changed) {
+                uploads.push(Upload::script(script, version));
+                report.pushed += 1;
+                continue;
+            }
+            let Some((theirs, version)) = downloads.remove(id).filter(|_| remote_changed) else {
+                continue;
+            };
+            if !local_changed {
+                if theirs.deleted {
+                    tx.execute("DELETE FROM scripts WHERE id = ?1", rusqlite::params![id])
+                        .map_err(|e| e.to_string())?;
+                    save_state(tx, id, DELETED, &version)?;
+                    report.deleted_local += 1;
+                } else {
+                    tx.execute(
+                        "UPDATE scripts SET title = ?1, content = ?2, updated_at = ?3 WHERE id = ?4",
+                        rusqlite::params![theirs.title, vault.seal(&theirs.content)?, theirs.updated_at, id],
+                    )
+                    .map_err(|e| e.to_string())?;
+                    save_state(tx, id, &theirs.updated_at, &version)?;
+                    report.pulled += 1;
+                }
+                continue;
+            }
+
+            // Changed on both sides. The remote version is merged now; the
+            // local one stays marked unsynced until its upload lands, so a
+            // failed upload is retried rather than read as a new conflict
+            if theirs.deleted {
+                save_state(tx, id, UNSYNCED, &version)?;
+                uploads.push(Upload::script(script, Some(&version)));
+                report.conflicts.push(SyncConflict {
+                    script_id: id.clone(),
+                    title: script.title.clone(),
+                    resolution: ConflictResolution::RestoredLocal,
+                });
+            } else if theirs.title == script.title && theirs.content == script.content {
+                save_state(tx, id, &script.updated_at, &version)?;
+            } else {
+                let now = chrono::Utc::now().to_rfc3339();
+                let copy = Script {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    title: format!(
+                        "{} (conflict copy {})",
+                        theirs.title,
+                        chrono::Local::now().format("%Y-%m-%d %H:%M")
+                    ),
+                    content: theirs.content,
+                    created_at: now.clone(),
+                    updated_at: now,
+                    word_count: None,
+                    estimated_seconds: None,
+                };
+                insert_script(tx, vault, &copy)?;
+                save_state(tx, id, UNSYNCED, &version)?;
+                uploads.push(Upload::script(&copy, None));
+                uploads.push(Upload::script(script, Some(&version)));
+                report.conflicts.push(SyncConflict {
+                    script_id: id.clone(),
+                    title: script.title.clone(),
+                    resolution: ConflictResolution::KeptBoth,
+                });
+            }
+            continue;
+        }
+
+        if version.is_none() {
+            // Never reached the server, or removed from it by hand
+            if state.is_some() {
+                delete_state(tx, id)?;
+            }
+            continue;
+        }
+
+        if let Some(deleted_at) = tombstones.get(id).filter(|_| !remote_changed) {
+            if state.is_some_and(|s| s.synced_updated_at == DELETED) {
+                continue;
+            }
+            uploads.push(Upload {
+                script: RemoteScript {
+                    id: id.clone(),
+                    title: String::new(),
+                    content: String::new(),
+                    created_at: String::new(),
+                    updated_at: deleted_at.clone(),
+                    deleted: true,
+                },
+                expected: version.cloned(),
+                synced_updated_at: DELETED.to_string(),
+            });
+            report.deleted_remote += 1;
+            continue;
+        }
+
+        // Nothing new on the server for a script this machine doesn't have
+        let Some((theirs, version)) = downloads.remove(id) else {
+            continue;
+        };
+        if theirs.deleted {
+            save_state(tx, id, DELETED, &version)?;
+            continue;
+        }
+        let script = Script {
+            id: id.clone(),
+            title: theirs.title,
+            content: theirs.content,
+            created_at: theirs.created_at,
+            updated_at: theirs.updated_at,
+            word_count: None,
+            estimated_seconds: None,
+        };
+        insert_script(tx, vault, &script)?;
+        save_state(tx, id, &script.updated_at, &version)?;
+        if tombstones.contains_key(id) {
+            report.conflicts.push(SyncConflict {
+                script_id: id.clone(),
+                title: script.title,
+                resolution: ConflictResolution::RestoredRemote,
+            });
+        } else {
+            report.pulled += 1;
+        }
+    }
+
+    Ok((report, uploads))
+}
+
+fn load_tombstones(conn: &Connection) -> Result<HashMap<String, String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT script_id, deleted_at FROM tombstones")
+        .map_err(|e| e.to_string())?;
+    let tombstones = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(tombstones)
+}
+
+fn load_states(conn: &Connection) -> Result<HashMap<String, SyncState>, String> {
+    let mut stmt = conn
+        .prepare("SELECT script_id, synced_updated_at, remote_version FROM webdav_sync_state")
+        .map_err(|e| e.to_string())?;
+    let states = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                SyncState {
+                    synced_updated_at: row.get(1)?,
+                    remote_version: row.get(2)?,
+                },
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(states)
+}
+
+fn save_state(conn: &Connection, id: &str, synced_updated_at: &str, remote_version: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR REPLACE INTO webdav_sync_state (script_id, synced_updated_at, remote_version) VALUES (?1, ?2, ?3)",
+        rusqlite::params![id, synced_updated_at, remote_version],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn delete_state(conn: &Connection, id: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM webdav_sync_state WHERE script_id = ?1", rusqlite::params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:"><d:prop><d:getetag/><d:getlastmodified/></d:prop></d:propfind>"#;
+
+/// `RemoteStore` backed by a WebDAV server.
+pub struct WebDavStore {
+    agent: ureq::Agent,
+    base: String,
+    auth: Option<String>,
+}
+
+type Response = ureq::http::Response<ureq::Body>;
+
+impl WebDavStore {
+    pub fn new(config: &WebDavConfig) -> Result<Self, String> {
+        config.validate()?;
+        let agent = ureq::Agent::config_builder()
+            .allow_non_standard_methods(true)
+            .http_status_as_error(false)
+            .timeout_global(Some(Duration::from_secs(30)))
+            .build()
+            .new_agent();
+
+        let mut base = config.url.trim().to_string();
+        if !base.ends_with('/') {
+            base.push('/');
+        }
+
+        let auth = (!config.username.is_empty()).then(|| {
+            let credentials = format!("{}:{}", config.username, config.password);
+            format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(credentials))
+        });
+
+        Ok(Self { agent, base, auth })
+    }
+
+    fn send(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: impl ureq::AsSendBody,
+    ) -> Result<Response, String> {
+        let mut builder = ureq::http::Request::builder().method(method).uri(url);
+        if let Some(auth) = &self.auth {
+            builder = builder.header("Authorization", auth);
+        }
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        let request = builder.body(body).map_err(|e| e.to_string())?;
+        self.agent.run(request).map_err(|e| e.to_string())
+    }
+
+    fn file_url(&self, id: &str) -> String {
+        format!("{}{}.json", self.base, id)
+    }
+}
+
+fn check(method: &str, response: &Response) -> Result<(), String> {
+    let status = response.status();
+    if status.is_success() || status.as_u16() == 207 {
+        Ok(())
+    } else {
+        Err(format!("WebDAV {} failed: HTTP {}", method, status.as_u16()))
+    }
+}
+
+/// The header that makes a PUT fail with 412 if the file isn't at
+/// `expected`: the ETag, or the Last-Modified date for servers without
+/// ETags. No file at all is expected for a new script.
+fn precondition(expected: Option<&str>) -> Option<(&'static str, &str)> {
+    match expected {
+        None => Some(("If-None-Match", "*")),
+        Some(version) if version.starts_with('"') || version.starts_with("W/") => Some(("If-Match", version)),
+        Some(version) if chrono::DateTime::parse_from_rfc2822(version).is_ok() => {
+            Some(("If-Unmodified-Since", version))
+        }
+        Some(_) => None,
+    }
+}
+
+/// ETag, falling back to Last-Modified for servers without ETags.
+fn version_header(response: &Response) -> Option<String> {
+    let headers = response.headers();
+    headers
+        .get("ETag")
+        .or_else(|| headers.get("Last-Modified"))
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+impl RemoteStore for WebDavStore {
+    fn list(&self) -> Result<HashMap<String, String>, String> {
+        let mut response = self.send(
+            "PROPFIND",
+            &self.base,
+            &[("Depth", "1"), ("Content-Type", "application/xml; charset=utf-8")],
+            PROPFIND_BODY,
+        )?;
+
+        if response.status().as_u16() == 404 {
+            // First sync against this server: create the collection
+            let created = self.send("MKCOL", &self.base, &[], ())?;
+            check("MKCOL", &created)?;
+            return Ok(HashMap::new());
+        }
+        check("PROPFIND", &response)?;
+
+        let xml = response.body_mut().read_to_string().map_err(|e| e.to_string())?;
+        Ok(parse_multistatus(&xml))
+    }
+
+    fn get(&self, id: &str) -> Result<(RemoteScript, String), String> {
+        let mut response = self.send("GET", &self.file_url(id), &[], ())?;
+        check("GET", &response)?;
+        let version = version_header(&response).unwrap_or_default();
+        let body = response.body_mut().read_to_string().map_err(|e| e.to_string())?;
+        let script = serde_json::from_str(&body).map_err(|e| e.to_string())?;
+        Ok((script, version))
+    }
+
+    fn put(&self, script: &RemoteScript, expected: Option<&str>) -> Result<Option<String>, String> {
+        let url = self.file_url(&script.id);
+        let body = serde_json::to_vec(script).map_err(|e| e.to_string())?;
+        let mut headers = vec![("Content-Type", "application/json")];
+        headers.extend(precondition(expected));
+        let response = self.send("PUT", &url, &headers, body)?;
+        if response.status().as_u16() == 412 {
+            return Ok(None);
+        }
+        check("PUT", &response)?;
+
+        if let Some(version) = version_header(&response) {
+            return Ok(Some(version));
+        }
+        // Not every server returns the new ETag from PUT
+        let head = self.send("HEAD", &url, &[], ())?;
+        check("HEAD", &head)?;
+        version_header(&head)
+            .map(Some)
+            .ok_or_else(|| "WebDAV server returned no ETag".to_string())
+    }
+}
+
+/// Extract `<id>.json` entries and their versions from a PROPFIND response.
+fn parse_multistatus(xml: &str) -> HashMap<String, String> {
+    let mut versions = HashMap::new();
+    for response in elements(xml, "response") {
+        let Some(href) = elements(response, "href").first().map(|h| h.trim()) else {
+            continue;
+        };
+        let Some(id) = href
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .and_then(|name| name.strip_suffix(".json"))
+        else {
+            continue;
+        };
+        let version = elements(response, "getetag")
+            .into_iter()
+            .next()
+            .or_else(|| elements(response, "getlastmodified").into_iter().next())
+            .map(|v| v.trim().replace("&quot;", "\""));
+        if let Some(version) = version.filter(|v| !v.is_empty()) {
+            versions.insert(id.to_string(), version);
+        }
+    }
+    versions
+}
+
+/// Inner text of every element with the given local name, whatever
+/// namespace prefix the server chose.
+fn elements<'a>(xml: &'a str, local: &str) -> Vec<&'a str> {
+    let mut found = Vec::new();
+    let mut rest = xml;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[..end];
+        let name = tag.split_whitespace().next().unwrap_or("");
+        let name_local = name.rsplit(':').next().unwrap_or(name);
+        if tag.starts_with('/') || tag.ends_with('/') || name_local != local {
+            continue;
+        }
+
+        let body = &rest[end + 1..];
+        let close = format!("</{}>", name);
+        if let Some(close_at) = body.find(&close) {
+            found.push(&body[..close_at]);
+            rest = &body[close_at + close.len()..];
+        }
+    }
+
+    found
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WebDavStatus {
+    pub url: Option<String>,
+    pub username: Option<String>,
+    pub interval_minutes: Option<u32>,
+    pub last_synced_at: Option<String>,
+    pub last_error: Option<String>,
+    pub last_report: Option<WebDavReport>,
+}
+
+/// Holds the result of the last pass and the background timer, if any.
+#[derive(Default)]
+pub struct WebDavSync {
+    status: Mutex<WebDavStatus>,
+    // Held for a whole pass, so the timer and Sync Now never overlap
+    pass: Mutex<()>,
+    // Bumped whenever the config changes so stale timer threads exit
+    generation: Mutex<u64>,
+}
+
+impl WebDavSync {
+    pub fn status(&self) -> WebDavStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Apply a new config. Nothing touches the network until a config is set.
+    pub fn configure(&self, app: &AppHandle, config: Option<&WebDavConfig>) {
+        let generation = {
+            let mut generation = self.generation.lock().unwrap();
+            *generation += 1;
+            *generation
+        };
+        *self.status.lock().unwrap() = WebDavStatus {
+            url: config.map(|c| c.url.clone()),
+            username: config.map(|c| c.username.clone()).filter(|u| !u.is_empty()),
+            interval_minutes: config.and_then(|c| c.interval_minutes),
+            ..Default::default()
+        };
+
+        let Some(minutes) = config.and_then(|c| c.interval_minutes).filter(|m| *m > 0) else {
+            return;
+        };
+        let app_handle = app.clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(Duration::from_secs(minutes as u64 * 60));
+            let state = app_handle.state::<AppState>();
+            if *state.webdav_sync.generation.lock().unwrap() != generation {
+                break;
+            }
+            sync_now(&app_handle).ok();
+        });
+    }
+}
+
+/// Start the background timer for the configured server, if any.
+pub fn resume(app: &AppHandle) {
+    let config = super::SyncConfig::load();
+    // Older versions kept the password in sync.json; saving drops it there
+    if let Some(webdav) = config.webdav.as_ref().filter(|c| !c.password.is_empty()) {
+        if webdav.store_password().is_ok() {
+            config.save().ok();
+        }
+    }
+    app.state::<AppState>().webdav_sync.configure(app, config.webdav.as_ref());
+}
+
+/// Run one pass against the configured server.
+pub fn sync_now(app: &AppHandle) -> Result<WebDavReport, String> {
+    let state = app.state::<AppState>();
+    state.db.ensure_writable()?;
+    let config = super::SyncConfig::load()
+        .webdav
+        .ok_or("WebDAV sync is not configured")?;
+    let store = WebDavStore::new(&config.with_password()?)?;
+
+    let result = {
+        let _pass = state.webdav_sync.pass.lock().map_err(|e| e.to_string())?;
+        sync(&state.db.conn, &state.db.vault, &store)
+    };
+
+    {
+        let mut status = state.webdav_sync.status.lock().unwrap();
+        status.last_synced_at = Some(chrono::Utc::now().to_rfc3339());
+        match &result {
+            Ok(report) => {
+                status.last_error = None;
+                status.last_report = Some(report.clone());
+            }
+            Err(e) => status.last_error = Some(e.clone()),
+        }
+    }
+
+    if let Ok(report) = &result {
+        if report.changed_library() {
+            state.folder_sync.request_sync();
+            app.emit("library-changed", ()).ok();
+            crate::tray::refresh(app);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    /// In-memory stand-in for a WebDAV collection.
+    #[derive(Default)]
+    struct MemoryStore {
+        files: RefCell<HashMap<String, (RemoteScript, u32)>>,
+        offline_uploads: Cell<bool>,
+    }
+
+    impl RemoteStore for MemoryStore {
+        fn list(&self) -> Result<HashMap<String, String>, String> {
+            Ok(self
+                .files
+                .borrow()
+                .iter()
+                .map(|(id, (_, rev))| (id.clone(), rev.to_string()))
+                .collect())
+        }
+
+        fn get(&self, id: &str) -> Result<(RemoteScript, String), String> {
+            let files = self.files.borrow();
+            let (script, rev) = files.get(id).ok_or("not found")?;
+            Ok((script.clone(), rev.to_string()))
+        }
+
+        fn put(&self, script: &RemoteScript, expected: Option<&str>) -> Result<Option<String>, String> {
+            if self.offline_uploads.get() {
+                return Err("offline".into());
+            }
+            let mut files = self.files.borrow_mut();
+            let current = files.get(&script.id).map(|(_, rev)| rev.to_string());
+            if current.as_deref() != expected {
+                return Ok(None);
+            }
+            let rev = files.get(&script.id).map_or(1, |(_, rev)| rev + 1);
+            files.insert(script.id.clone(), (script.clone(), rev));
+            Ok(Some(rev.to_string()))
+        }
+    }
+
+    fn library() -> Mutex<Connection> {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::apply_migrations(&conn).unwrap();
+        Mutex::new(conn)
+    }
+
+    fn add(db: &Mutex<Connection>, id: &str, content: &str) {
+        insert_script(
+            &db.lock().unwrap(),
+            &Vault::disabled(),
+            &Script {
+                id: id.into(),
+                title: id.into(),
+                content: content.into(),
+                created_at: "2026-01-01T00:00:00Z".into(),
+                updated_at: "2026-01-01T00:00:00Z".into(),
+                word_count: None,
+                estimated_seconds: None,
+            },
+        )
+        .unwrap();
+    }
+
+    fn edit(db: &Mutex<Connection>, id: &str, content: &str) {
+        db.lock().unwrap().execute(
+            "UPDATE scripts SET content = ?1, updated_at = ?2 WHERE id = ?3",
+            rusqlite::params![content, chrono::Utc::now().to_rfc3339(), id],
+        )
+        .unwrap();
+    }
+
+    fn content(db: &Mutex<Connection>, id: &str) -> Option<String> {
+        db.lock()
+            .unwrap()
+            .query_row("SELECT content FROM scripts WHERE id = ?1", [id], |row| row.get(0))
+            .ok()
+    }
+
+    #[test]
+    fn edits_travel_between_machines() {
+        let server = MemoryStore::default();
+        let studio = library();
+        let laptop = library();
+
+        add(&studio, "a", "first draft");
+        assert_eq!(sync(&studio, &Vault::disabled(), &server).unwrap().pushed, 1);
+        assert_eq!(sync(&laptop, &Vault::disabled(), &server).unwrap().pulled, 1);
+        assert_eq!(content(&laptop, "a").as_deref(), Some("first draft"));
+
+        edit(&laptop, "a", "second draft");
+        sync(&laptop, &Vault::disabled(), &server).unwrap();
+        let report = sync(&studio, &Vault::disabled(), &server).unwrap();
+        assert_eq!(report.pulled, 1);
+        assert_eq!(content(&studio, "a").as_deref(), Some("second draft"));
+
+        // Nothing left to do once both sides agree
+        let report = sync(&studio, &Vault::disabled(), &server).unwrap();
+        assert_eq!((report.pushed, report.pulled), (0, 0));
+    }
+
+    #[test]
+    fn deletes_propagate_as_tombstones() {
+        let server = MemoryStore::default();
+        let studio = library();
+        let laptop = library();
+
+        add(&studio, "a", "text");
+        sync(&studio, &Vault::disabled(), &server).unwrap();
+        sync(&laptop, &Vault::disabled(), &server).unwrap();
+
+        studio.lock().unwrap().execute("DELETE FROM scripts WHERE id = 'a'", []).unwrap();
+        assert_eq!(sync(&studio, &Vault::disabled(), &server).unwrap().deleted_remote, 1);
+        assert!(server.get("a").unwrap().0.deleted);
+
+        assert_eq!(sync(&laptop, &Vault::disabled(), &server).unwrap().deleted_local, 1);
+        assert_eq!(content(&laptop, "a"), None);
+        assert_eq!(sync(&laptop, &Vault::disabled(), &server).unwrap().deleted_remote, 0);
+    }
+
+    #[test]
+    fn concurrent_edits_keep_both_versions() {
+        let server = MemoryStore::default();
+        let studio = library();
+        let laptop = library();
+
+        add(&studio, "a", "original");
+        sync(&studio, &Vault::disabled(), &server).unwrap();
+        sync(&laptop, &Vault::disabled(), &server).unwrap();
+
+        edit(&studio, "a", "studio edit");
+        edit(&laptop, "a", "laptop edit");
+        sync(&studio, &Vault::disabled(), &server).unwrap();
+        let report = sync(&laptop, &Vault::disabled(), &server).unwrap();
+
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].resolution, ConflictResolution::KeptBoth);
+        assert_eq!(content(&laptop, "a").as_deref(), Some("laptop edit"));
+        let copies: i64 = laptop
+            .lock()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM scripts WHERE content = 'studio edit'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(copies, 1);
+    }
+
+    #[test]
+    fn remote_edit_restores_local_delete() {
+        let server = MemoryStore::default();
+        let studio = library();
+        let laptop = library();
+
+        add(&studio, "a", "original");
+        sync(&studio, &Vault::disabled(), &server).unwrap();
+        sync(&laptop, &Vault::disabled(), &server).unwrap();
+
+        edit(&studio, "a", "studio edit");
+        sync(&studio, &Vault::disabled(), &server).unwrap();
+        laptop.lock().unwrap().execute("DELETE FROM scripts WHERE id = 'a'", []).unwrap();
+
+        let report = sync(&laptop, &Vault::disabled(), &server).unwrap();
+        assert_eq!(report.conflicts[0].resolution, ConflictResolution::RestoredRemote);
+        assert_eq!(content(&laptop, "a").as_deref(), Some("studio edit"));
+    }
+
+    #[test]
+    fn parses_propfind_responses_with_any_prefix() {
+        let xml = r#"<?xml version="1.0"?>
+<D:multistatus xmlns:D="DAV:">
+  <D:response><D:href>/dav/whispr/</D:href><D:propstat><D:prop/></D:propstat></D:response>
+  <D:response><D:href>/dav/whispr/abc.json</D:href>
+    <D:propstat><D:prop><D:getetag>&quot;v1&quot;</D:getetag></D:prop></D:propstat></D:response>
+  <response xmlns="DAV:"><href>/dav/whispr/def.json</href>
+    <propstat><prop><getlastmodified>Sun, 18 Oct 2026 10:00:00 GMT</getlastmodified></prop></propstat></response>
+</D:multistatus>"#;
+
+        let versions = parse_multistatus(xml);
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions["abc"], "\"v1\"");
+        assert_eq!(versions["def"], "Sun, 18 Oct 2026 10:00:00 GMT");
+    }
+
+    #[test]
+    fn an_encrypted_library_is_not_uploaded() {
+        let server = MemoryStore::default();
+        let studio = library();
+        add(&studio, "a", "secret");
+        let vault = Vault::disabled();
+        vault.enable(&mut studio.lock().unwrap(), "correct horse").unwrap();
+
+        assert!(sync(&studio, &vault, &server).is_err());
+        assert!(server.files.borrow().is_empty());
+    }
+
+    #[test]
+    fn failed_uploads_are_retried_without_a_new_conflict() {
+        let server = MemoryStore::default();
+        let studio = library();
+        let laptop = library();
+
+        add(&studio, "a", "original");
+        sync(&studio, &Vault::disabled(), &server).unwrap();
+        sync(&laptop, &Vault::disabled(), &server).unwrap();
+
+        edit(&studio, "a", "studio edit");
+        edit(&laptop, "a", "laptop edit");
+        sync(&studio, &Vault::disabled(), &server).unwrap();
+        server.offline_uploads.set(true);
+        assert!(sync(&laptop, &Vault::disabled(), &server).is_err());
+        // The merge committed before the uploads failed
+        assert_eq!(content(&laptop, "a").as_deref(), Some("laptop edit"));
+
+        server.offline_uploads.set(false);
+        let report = sync(&laptop, &Vault::disabled(), &server).unwrap();
+        assert!(report.conflicts.is_empty());
+        assert_eq!(report.pushed, 2);
+        assert_eq!(server.get("a").unwrap().0.content, "laptop edit");
+    }
+
+    struct Request {
+        method: String,
+        path: String,
+        headers: HashMap<String, String>,
+        body: String,
+    }
+
+    /// A local HTTP server that answers one request per connection with each
+    /// of `responses` in turn, then hands back the requests it got.
+    fn serve(responses: Vec<String>) -> (String, JoinHandle<Vec<Request>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/dav/whispr", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for response in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap().to_string();
+                let path = parts.next().unwrap().to_string();
+                let mut headers = HashMap::new();
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+                    }
+                }
+                let length = headers.get("content-length").map_or(0, |l| l.parse().unwrap());
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+                requests.push(Request {
+                    method,
+                    path,
+                    headers,
+                    body: String::from_utf8(body).unwrap(),
+                });
+            }
+            requests
+        });
+        (url, handle)
+    }
+
+    fn response(status: &str, headers: &[&str], body: &str) -> String {
+        let mut text = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n", status, body.len());
+        for header in headers {
+            text.push_str(header);
+            text.push_str("\r\n");
+        }
+        text.push_str("\r\n");
+        text.push_str(body);
+        text
+    }
+
+    fn store_config(url: String) -> WebDavConfig {
+        WebDavConfig {
+            url,
+            username: "user".into(),
+            password: "pass".into(),
+            interval_minutes: None,
+        }
+    }
+
+    fn store(url: String) -> WebDavStore {
+        WebDavStore::new(&store_config(url)).unwrap()
+    }
+
+    #[test]
+    fn first_listing_creates_the_collection() {
+        let (url, server) = serve(vec![response("404 Not Found", &[], ""), response("201 Created", &[], "")]);
+        assert!(store(url).list().unwrap().is_empty());
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0].method, "PROPFIND");
+        assert_eq!(requests[0].path, "/dav/whispr/");
+        assert_eq!(requests[0].headers["depth"], "1");
+        assert!(requests[0].body.contains("getetag"));
+        assert_eq!(requests[1].method, "MKCOL");
+        assert_eq!(requests[1].path, "/dav/whispr/");
+        // user:pass
+        for request in &requests {
+            assert_eq!(request.headers["authorization"], "Basic dXNlcjpwYXNz");
+        }
+    }
+
+    #[test]
+    fn listing_reads_the_multistatus() {
+        let xml = r#"<d:multistatus xmlns:d="DAV:"><d:response><d:href>/dav/whispr/a.json</d:href>
+<d:propstat><d:prop><d:getetag>"v3"</d:getetag></d:prop></d:propstat></d:response></d:multistatus>"#;
+        let (url, server) = serve(vec![response("207 Multi-Status", &[], xml)]);
+        let versions = store(url).list().unwrap();
+        assert_eq!(versions["a"], "\"v3\"");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn uploads_fall_back_to_head_for_the_version() {
+        let (url, server) = serve(vec![
+            response("201 Created", &[], ""),
+            response("200 OK", &["ETag: \"v1\""], ""),
+        ]);
+        let script = RemoteScript {
+            id: "a".into(),
+            title: "Intro".into(),
+            content: "Hello".into(),
+            created_at: "2026-01-01T00:00:00Z".into(),
+            updated_at: "2026-01-01T00:00:00Z".into(),
+            deleted: false,
+        };
+        assert_eq!(store(url).put(&script, None).unwrap().as_deref(), Some("\"v1\""));
+
+        let requests = server.join().unwrap();
+        assert_eq!((requests[0].method.as_str(), requests[0].path.as_str()), ("PUT", "/dav/whispr/a.json"));
+        assert_eq!(requests[0].headers["content-type"], "application/json");
+        assert_eq!(requests[0].headers["if-none-match"], "*");
+        let body: RemoteScript = serde_json::from_str(&requests[0].body).unwrap();
+        assert_eq!(body.content, "Hello");
+        assert_eq!((requests[1].method.as_str(), requests[1].path.as_str()), ("HEAD", "/dav/whispr/a.json"));
+    }
+
+    #[test]
+    fn uploads_are_refused_if_the_file_changed_since_listing() {
+        let (url, server) = serve(vec![response("412 Precondition Failed", &[], "")]);
+        let script = RemoteScript {
+            id: "a".into(),
+            title: "Intro".into(),
+            content: "Hello".into(),
+            created_at: "2026-01-01T00:00:00Z".into(),
+            updated_at: "2026-01-01T00:00:00Z".into(),
+            deleted: false,
+        };
+        assert_eq!(store(url).put(&script, Some("\"v1\"")).unwrap(), None);
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0].method, "PUT");
+        assert_eq!(requests[0].headers["if-match"], "\"v1\"");
+    }
+
+    #[test]
+    fn downloads_carry_their_version() {
+        let body = r#"{"id":"a","title":"Intro","content":"Hello","created_at":"","updated_at":""}"#;
+        let (url, server) = serve(vec![response("200 OK", &["ETag: \"v2\""], body)]);
+        let (script, version) = store(url).get("a").unwrap();
+        assert_eq!((script.content.as_str(), version.as_str()), ("Hello", "\"v2\""));
+        assert!(!script.deleted);
+        assert_eq!(server.join().unwrap()[0].path, "/dav/whispr/a.json");
+    }
+
+    #[test]
+    fn server_errors_name_the_status() {
+        let (url, server) = serve(vec![response("401 Unauthorized", &[], "")]);
+        let error = store(url).list().unwrap_err();
+        assert_eq!(error, "WebDAV PROPFIND failed: HTTP 401");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn passwords_only_travel_over_https_or_to_this_machine() {
+        let config = |url: &str| store_config(url.into());
+        assert!(config("https://dav.example.com/whispr/").validate().is_ok());
+        assert!(config("http://dav.example.com/whispr/").validate().is_err());
+        assert!(config("http://localhost:8080/dav").validate().is_ok());
+        assert!(config("http://127.0.0.1/dav").validate().is_ok());
+        assert!(config("http://[::1]:8080/dav").validate().is_ok());
+        // Userinfo doesn't make a remote host local
+        assert!(config("http://localhost@dav.example.com/").validate().is_err());
+        assert!(config("ftp://dav.example.com/").validate().is_err());
+        let anonymous = WebDavConfig {
+            username: String::new(),
+            ..config("http://dav.example.com/whispr/")
+        };
+        assert!(anonymous.validate().is_ok());
+    }
+
+    #[test]
+    fn passwords_stay_out_of_sync_json() {
+        let config = store_config("https://dav.example.com/whispr/".into());
+        let saved = serde_json::to_value(&config).unwrap();
+        assert!(saved.get("password").is_none());
+        assert_eq!(saved["username"], "user");
+        // A sync.json from before the keyring still loads its password
+        let old: WebDavConfig = serde_json::from_str(r#"{"url":"u","password":"pass"}"#).unwrap();
+        assert_eq!(old.password, "pass");
+    }
+}
@@ -0,0 +1,152 @@
+//! Display helpers for user-written text such as script titles, and word
+//! counting for reading-time estimates.
+//!
+//! Titles are cut at grapheme boundaries, so an emoji sequence, a letter with
+//! combining marks or a Hangul syllable is never split, and measured in
+//! terminal-style columns, where CJK characters and most emoji take two.
+//!
+//! Chinese and Japanese are written without spaces, so each ideograph or
+//! kana counts as a word; the prompter engine (`Utils.wordPieces`) splits
+//! text the same way.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+const ELLIPSIS: &str = "...";
+
+/// Columns `text` takes up on screen.
+pub fn display_width(text: &str) -> usize {
+    text.graphemes(true).map(grapheme_width).sum()
+}
+
+/// Shorten `text` to at most `max_width` columns, ending in "..." when
+/// anything was cut. Below three columns only as many dots as fit are left.
+pub fn truncate(text: &str, max_width: usize) -> String {
+    if display_width(text) <= max_width {
+        return text.to_string();
+    }
+    if max_width < ELLIPSIS.len() {
+        return ".".repeat(max_width);
+    }
+
+    let budget = max_width.saturating_sub(ELLIPSIS.len());
+    let mut width = 0;
+    let mut end = 0;
+    for (index, grapheme) in text.grapheme_indices(true) {
+        width += grapheme_width(grapheme);
+        if width > budget {
+            break;
+        }
+        end = index + grapheme.len();
+    }
+    format!("{}{}", text[..end].trim_end(), ELLIPSIS)
+}
+
+/// Words as the prompter paces them: whitespace-separated, except that each
+/// CJK character is a word of its own. Punctuation right after a CJK
+/// character belongs to it.
+pub fn word_count(text: &str) -> usize {
+    text.split_whitespace().map(token_words).sum()
+}
+
+fn token_words(token: &str) -> usize {
+    let mut count = 0;
+    let mut in_run = false;
+    for c in token.chars() {
+        if is_cjk(c) {
+            count += 1;
+            in_run = false;
+        } else if !in_run && (count == 0 || c.is_alphanumeric()) {
+            count += 1;
+            in_run = true;
+        }
+    }
+    count
+}
+
+/// Han ideographs, hiragana and katakana. Hangul is written with spaces.
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'
+        | '\u{31F0}'..='\u{31FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FF66}'..='\u{FF9F}'
+        | '\u{20000}'..='\u{2FA1F}')
+}
+
+/// A cluster is as wide as it renders: a ZWJ emoji sequence is one
+/// two-column glyph, not the sum of its parts.
+fn grapheme_width(grapheme: &str) -> usize {
+    grapheme.width().min(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_titles_are_unchanged() {
+        assert_eq!(truncate("Keynote", 30), "Keynote");
+        assert_eq!(truncate("", 30), "");
+    }
+
+    #[test]
+    fn ascii_is_cut_to_width() {
+        let title = "Quarterly results for the board meeting";
+        let cut = truncate(title, 30);
+        assert_eq!(cut, "Quarterly results for the b...");
+        assert_eq!(display_width(&cut), 30);
+    }
+
+    #[test]
+    fn cjk_counts_two_columns() {
+        assert_eq!(display_width("日本語"), 6);
+        let cut = truncate("日本語のスクリプトのタイトルです", 12);
+        assert_eq!(cut, "日本語の...");
+        assert!(display_width(&cut) <= 12);
+    }
+
+    #[test]
+    fn emoji_sequences_are_kept_whole() {
+        let family = "👨‍👩‍👧‍👦";
+        assert_eq!(display_width(family), 2);
+        let cut = truncate(&format!("{} {} {} talk", family, family, family), 7);
+        assert_eq!(cut, format!("{}...", family));
+
+        let flags = "🇯🇵🇫🇷🇧🇷🇺🇸";
+        let cut = truncate(flags, 7);
+        assert_eq!(cut, "🇯🇵🇫🇷...");
+    }
+
+    #[test]
+    fn rtl_and_combining_marks_are_not_split() {
+        let hebrew = "שָׁלוֹם עֲלֵיכֶם וּבְרָכָה";
+        let cut = truncate(hebrew, 10);
+        assert!(cut.ends_with(ELLIPSIS));
+        assert!(hebrew.starts_with(cut.trim_end_matches(ELLIPSIS)));
+        assert!(display_width(&cut) <= 10);
+
+        let arabic = "مرحبا بكم في العرض التقديمي";
+        let cut = truncate(arabic, 12);
+        assert_eq!(cut, "مرحبا بكم...");
+    }
+
+    #[test]
+    fn words_are_counted_per_cjk_character() {
+        assert_eq!(word_count("  Hello there,  world! "), 3);
+        assert_eq!(word_count("— and"), 2);
+        assert_eq!(word_count("日本語です。"), 5);
+        assert_eq!(word_count("こんにちは、Whispr!"), 6);
+        assert_eq!(word_count("안녕하세요 여러분"), 2);
+        assert_eq!(word_count(""), 0);
+    }
+
+    #[test]
+    fn tiny_widths_never_go_over() {
+        assert_eq!(truncate("日本語", 3), "...");
+        assert_eq!(truncate("Whispr", 2), "..");
+        assert_eq!(truncate("Whispr", 0), "");
+    }
+}
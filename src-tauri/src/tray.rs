@@ -1,25 +1,47 @@
 use tauri::{
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    menu::{MenuBuilder, MenuItemBuilder, PredefinedMenuItem},
+    menu::{CheckMenuItemBuilder, MenuBuilder, MenuItemBuilder, PredefinedMenuItem, SubmenuBuilder},
     AppHandle, Emitter, Manager,
 };
+use crate::error::{AppResult, ErrorCode};
+use crate::models::{PrompterPhase, PrompterStatus};
+use crate::profiles::Profiles;
 use crate::state::AppState;
 
+const TRAY_ID: &str = "whispr";
+
 pub fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
     let menu = build_tray_menu(app)?;
 
-    let _tray = TrayIconBuilder::new()
+    let _tray = TrayIconBuilder::with_id(TRAY_ID)
         .menu(&menu)
+        .icon(tauri::include_image!("icons/icon.png"))
+        .icon_as_template(true)
         .tooltip("Whispr")
         .on_menu_event(move |app, event| {
             match event.id().as_ref() {
                 "new_script" => {
+                    crate::vault::show_main(app);
                     if let Some(w) = app.get_webview_window("main") {
-                        w.show().ok();
-                        w.set_focus().ok();
                         w.emit("new-script", ()).ok();
                     }
                 }
+                "lock" => {
+                    crate::vault::lock_now(app);
+                }
+                "prompter_toggle" | "prompter_faster" | "prompter_slower" | "prompter_restart" => {
+                    let action = event.id().as_ref().trim_start_matches("prompter_");
+                    app.emit("prompter-control", action).ok();
+                }
+                "run_list_next" | "run_list_previous" => {
+                    let delta = if event.id().as_ref() == "run_list_next" { 1 } else { -1 };
+                    if let Some(state) = app.try_state::<AppState>() {
+                        crate::commands::run_lists::step(app, &state, delta).ok();
+                    }
+                }
+                "prompter_close" => {
+                    crate::commands::window::close_prompter(app.clone()).ok();
+                }
                 "settings" => {
                     crate::commands::window::open_settings_window(app.clone()).ok();
                 }
@@ -28,11 +50,24 @@ pub fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
                 }
                 _ => {
                     let id = event.id().as_ref().to_string();
-                    if id.starts_with("recent_") {
+                    if let Some(profile_id) = id.strip_prefix("profile_") {
+                        if let Some(state) = app.try_state::<AppState>() {
+                            crate::commands::profiles::activate_profile(app.clone(), state, profile_id.to_string()).ok();
+                        }
+                    } else if let Some(script_id) = id.strip_prefix("start_notch_") {
+                        if let Some(state) = app.try_state::<AppState>() {
+                            let result = crate::commands::window::open_topbar_prompter(app.clone(), state, script_id.to_string(), None);
+                            ask_for_variables(app, result, script_id, "notch");
+                        }
+                    } else if let Some(script_id) = id.strip_prefix("start_floating_") {
+                        if let Some(state) = app.try_state::<AppState>() {
+                            let result = crate::commands::window::open_floating_prompter(app.clone(), state, script_id.to_string(), None);
+                            ask_for_variables(app, result, script_id, "floating");
+                        }
+                    } else if id.starts_with("recent_") {
                         let script_id = id.strip_prefix("recent_").unwrap_or("");
+                        crate::vault::show_main(app);
                         if let Some(w) = app.get_webview_window("main") {
-                            w.show().ok();
-                            w.set_focus().ok();
                             w.emit("open-script", script_id).ok();
                         }
                     }
@@ -46,11 +81,7 @@ pub fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
                 ..
             } = event
             {
-                let app = tray.app_handle();
-                if let Some(w) = app.get_webview_window("main") {
-                    w.show().ok();
-                    w.set_focus().ok();
-                }
+                crate::vault::show_main(tray.app_handle());
             }
         })
         .build(app)?;
@@ -58,37 +89,158 @@ pub fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Rebuild the tray menu after the library, its lock state or the prompter changed.
+pub fn refresh(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    if let Ok(menu) = build_tray_menu(app) {
+        tray.set_menu(Some(menu)).ok();
+    }
+    show_status(app);
+}
+
+/// Show the prompter's phase in the tooltip and icon: a red badge while
+/// scrolling or counting down, an amber one while paused.
+pub fn show_status(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let status = app
+        .try_state::<AppState>()
+        .and_then(|state| state.prompter.lock().ok().map(|status| status.clone()))
+        .unwrap_or_default();
+
+    let (icon, template) = match status.phase {
+        PrompterPhase::CountingDown | PrompterPhase::Scrolling => {
+            (tauri::include_image!("icons/tray-live.png"), false)
+        }
+        PrompterPhase::Paused => (tauri::include_image!("icons/tray-paused.png"), false),
+        PrompterPhase::Idle | PrompterPhase::Finished => (tauri::include_image!("icons/icon.png"), true),
+    };
+    tray.set_icon(Some(icon)).ok();
+    tray.set_icon_as_template(template).ok();
+    tray.set_tooltip(Some(tooltip(&status))).ok();
+}
+
+/// The tray can't show the fill-in form, so a start refused for unfilled
+/// placeholders opens the main window and asks there.
+fn ask_for_variables(app: &AppHandle, result: AppResult<()>, script_id: &str, mode: &str) {
+    let Err(e) = result else {
+        return;
+    };
+    if e.code != ErrorCode::UnresolvedPlaceholders {
+        return;
+    }
+    crate::vault::show_main(app);
+    if let Some(w) = app.get_webview_window("main") {
+        w.emit("prompt-variables", serde_json::json!({ "scriptId": script_id, "mode": mode }))
+            .ok();
+    }
+}
+
+fn tooltip(status: &PrompterStatus) -> String {
+    let title = crate::text::truncate(&status.title, 40);
+    let percent = (status.progress * 100.0).round();
+    match status.phase {
+        PrompterPhase::Idle => "Whispr".to_string(),
+        PrompterPhase::CountingDown => format!("Whispr — Starting \"{}\"", title),
+        PrompterPhase::Scrolling => format!("Whispr — Live: \"{}\" ({}%)", title, percent),
+        PrompterPhase::Paused => format!("Whispr — Paused: \"{}\" ({}%)", title, percent),
+        PrompterPhase::Finished => format!("Whispr — Finished \"{}\"", title),
+    }
+}
+
 fn build_tray_menu(app: &AppHandle) -> Result<tauri::menu::Menu<tauri::Wry>, Box<dyn std::error::Error>> {
     let new_script = MenuItemBuilder::with_id("new_script", "New Script").build(app)?;
     let settings = MenuItemBuilder::with_id("settings", "Settings...").build(app)?;
     let quit = MenuItemBuilder::with_id("quit", "Quit Whispr").build(app)?;
 
     let mut builder = MenuBuilder::new(app);
+    let vault = app.try_state::<AppState>().map(|state| state.db.vault.status());
 
-    // Add recent scripts from DB
-    if let Some(state) = app.try_state::<AppState>() {
-        if let Ok(conn) = state.db.conn.lock() {
-            let recent = get_recent_titles(&conn);
-            if !recent.is_empty() {
-                for (id, title) in &recent {
-                    let menu_id = format!("recent_{}", id);
-                    let truncated = if title.len() > 30 {
-                        format!("{}...", &title[..27])
-                    } else {
-                        title.clone()
-                    };
-                    let item = MenuItemBuilder::with_id(menu_id, truncated).build(app)?;
-                    builder = builder.item(&item);
-                }
-                builder = builder.item(&PredefinedMenuItem::separator(app)?);
+    // Transport controls for the open prompter
+    let prompter = app
+        .try_state::<AppState>()
+        .and_then(|state| state.prompter.lock().ok().map(|status| status.clone()))
+        .filter(|status| status.phase != PrompterPhase::Idle);
+    if let Some(status) = prompter {
+        let heading = format!("Now Prompting — {} WPM", (status.speed * 3.0).round());
+        let toggle = if status.phase == PrompterPhase::Scrolling { "Pause" } else { "Resume" };
+        builder = builder
+            .item(&MenuItemBuilder::with_id("prompter_status", heading).enabled(false).build(app)?)
+            .item(&MenuItemBuilder::with_id("prompter_toggle", toggle).build(app)?)
+            .item(&MenuItemBuilder::with_id("prompter_faster", "Speed Up").build(app)?)
+            .item(&MenuItemBuilder::with_id("prompter_slower", "Slow Down").build(app)?)
+            .item(&MenuItemBuilder::with_id("prompter_restart", "Restart").build(app)?)
+            .item(&MenuItemBuilder::with_id("prompter_close", "Close Prompter").build(app)?);
+
+        // Stepping through a running run list
+        let run = app
+            .try_state::<AppState>()
+            .and_then(|state| state.run.lock().ok().and_then(|run| run.as_ref().map(|r| r.status())));
+        if let Some(run) = run {
+            let heading = format!("{} — {} of {}", crate::text::truncate(&run.name, 30), run.index + 1, run.count);
+            builder = builder
+                .item(&PredefinedMenuItem::separator(app)?)
+                .item(&MenuItemBuilder::with_id("run_list_status", heading).enabled(false).build(app)?)
+                .item(&MenuItemBuilder::with_id("run_list_next", "Next Script").build(app)?)
+                .item(&MenuItemBuilder::with_id("run_list_previous", "Previous Script").build(app)?);
+        }
+        builder = builder.item(&PredefinedMenuItem::separator(app)?);
+    }
+
+    // Add recent scripts from DB; titles stay hidden while the library is locked
+    if let Some(state) = app.try_state::<AppState>().filter(|state| !state.db.vault.is_locked()) {
+        let recent = match state.db.conn.lock() {
+            Ok(conn) => get_recent_titles(&conn),
+            Err(_) => Vec::new(),
+        };
+        if !recent.is_empty() {
+            let mut start = SubmenuBuilder::new(app, "Start Prompting");
+            for (id, title) in &recent {
+                let title = crate::text::truncate(title, 30);
+                let item = MenuItemBuilder::with_id(format!("recent_{}", id), &title).build(app)?;
+                builder = builder.item(&item);
+
+                let variants = SubmenuBuilder::new(app, &title)
+                    .item(&MenuItemBuilder::with_id(format!("start_notch_{}", id), "Top Bar").build(app)?)
+                    .item(&MenuItemBuilder::with_id(format!("start_floating_{}", id), "Floating").build(app)?)
+                    .build()?;
+                start = start.item(&variants);
             }
+            builder = builder
+                .item(&start.build()?)
+                .item(&PredefinedMenuItem::separator(app)?);
         }
     }
 
     builder = builder
         .item(&new_script)
-        .item(&PredefinedMenuItem::separator(app)?)
-        .item(&settings)
+        .item(&PredefinedMenuItem::separator(app)?);
+
+    // Profiles submenu, checked on the active one
+    let profiles = app
+        .try_state::<AppState>()
+        .and_then(|state| Profiles::load(&*state.db.conn.lock().ok()?).ok())
+        .map(|p| p.summaries())
+        .unwrap_or_default();
+    if !profiles.is_empty() {
+        let mut submenu = SubmenuBuilder::new(app, "Profiles");
+        for profile in &profiles {
+            let item = CheckMenuItemBuilder::with_id(format!("profile_{}", profile.id), &profile.name)
+                .checked(profile.active)
+                .build(app)?;
+            submenu = submenu.item(&item);
+        }
+        builder = builder.item(&submenu.build()?);
+    }
+
+    builder = builder.item(&settings);
+    if vault.is_some_and(|v| v.enabled && !v.locked) {
+        builder = builder.item(&MenuItemBuilder::with_id("lock", "Lock Library").build(app)?);
+    }
+    builder = builder
         .item(&PredefinedMenuItem::separator(app)?)
         .item(&quit);
 
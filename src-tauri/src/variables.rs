@@ -0,0 +1,63 @@
+//! Values for a script's `[Placeholder]`s, stored per script in
+//! `script_variables` and filled in when a prompter opens.
+//!
+//! Values are sealed by the vault like script content. A value is kept when
+//! the placeholder leaves the script, so it's still there if it comes back.
+
+use crate::error::AppResult;
+use crate::markup::{self, Document};
+use crate::vault::Vault;
+use rusqlite::{params, Connection, DatabaseName};
+use std::collections::HashMap;
+
+pub fn load(conn: &Connection, vault: &Vault, script_id: &str) -> AppResult<HashMap<String, String>> {
+    let mut stmt = match conn.prepare("SELECT name, value FROM script_variables WHERE script_id = ?1") {
+        Ok(stmt) => stmt,
+        // Opened read-only from before the table existed
+        Err(_) if conn.is_readonly(DatabaseName::Main)? => return Ok(HashMap::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let rows = stmt
+        .query_map(params![script_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    rows.into_iter()
+        .map(|(name, value)| Ok((name, vault.open(value)?)))
+        .collect()
+}
+
+/// Set the given values; an empty value clears that variable. Variables not
+/// mentioned are left alone.
+pub fn store(
+    conn: &Connection,
+    vault: &Vault,
+    script_id: &str,
+    values: &HashMap<String, String>,
+) -> AppResult<()> {
+    for (name, value) in values {
+        if value.trim().is_empty() {
+            conn.execute(
+                "DELETE FROM script_variables WHERE script_id = ?1 AND name = ?2",
+                params![script_id, name],
+            )?;
+        } else {
+            conn.execute(
+                "INSERT INTO script_variables (script_id, name, value) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(script_id, name) DO UPDATE SET value = excluded.value",
+                params![script_id, name, vault.seal(value)?],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// `content` parsed, with the script's stored values filled in.
+pub fn filled_document(
+    conn: &Connection,
+    vault: &Vault,
+    script_id: &str,
+    content: &str,
+) -> AppResult<Document> {
+    let mut document = markup::parse(content);
+    document.fill(&load(conn, vault, script_id)?);
+    Ok(document)
+}
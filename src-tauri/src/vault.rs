@@ -0,0 +1,507 @@
+//! Optional encryption of script content at rest.
+//!
+//! Content is encrypted with a random data key (ChaCha20-Poly1305). The data
+//! key is stored in the `vault` table wrapped by a key derived from the
+//! passphrase (Argon2id), so changing the passphrase only rewraps the data
+//! key. Encrypted values are stored as `enc:v1:<base64 nonce + ciphertext>`;
+//! anything without that prefix is plaintext. Script variable values are
+//! sealed the same way as script content.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::models::Script;
+use crate::state::AppState;
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+
+const PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 12;
+const PROMPTER_WINDOWS: [&str; 2] = ["topbar-prompter", "floating-prompter"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultStatus {
+    pub enabled: bool,
+    pub locked: bool,
+    pub auto_lock_minutes: Option<u32>,
+}
+
+struct Inner {
+    enabled: bool,
+    key: Option<Key>,
+    auto_lock: Option<Duration>,
+    last_activity: Instant,
+}
+
+pub struct Vault {
+    inner: Mutex<Inner>,
+}
+
+impl Vault {
+    pub fn disabled() -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                enabled: false,
+                key: None,
+                auto_lock: None,
+                last_activity: Instant::now(),
+            }),
+        }
+    }
+
+    /// Read the vault row. An encrypted library starts out locked.
+    pub fn load(conn: &Connection) -> rusqlite::Result<Self> {
+        let auto_lock: Option<Option<u32>> = conn
+            .query_row("SELECT auto_lock_minutes FROM vault WHERE id = 1", [], |row| row.get(0))
+            .optional()?;
+
+        let vault = Self::disabled();
+        if let Some(minutes) = auto_lock {
+            let mut inner = vault.inner.lock().unwrap();
+            inner.enabled = true;
+            inner.auto_lock = minutes.map(|m| Duration::from_secs(m as u64 * 60));
+        }
+        Ok(vault)
+    }
+
+    pub fn status(&self) -> VaultStatus {
+        let inner = self.inner.lock().unwrap();
+        VaultStatus {
+            enabled: inner.enabled,
+            locked: inner.enabled && inner.key.is_none(),
+            auto_lock_minutes: inner.auto_lock.map(|d| (d.as_secs() / 60) as u32),
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.status().locked
+    }
+
+    /// Encrypt content for storage. Plaintext passes through when encryption is off.
+    pub fn seal(&self, plaintext: &str) -> AppResult<String> {
+        let inner = self.inner.lock().unwrap();
+        if !inner.enabled {
+            return Ok(plaintext.to_string());
+        }
+        let key = inner.key.as_ref().ok_or_else(locked)?;
+        encrypt(key, plaintext.as_bytes())
+    }
+
+    /// Decrypt stored content. Plaintext values are returned as-is.
+    pub fn open(&self, stored: String) -> AppResult<String> {
+        if !stored.starts_with(PREFIX) {
+            return Ok(stored);
+        }
+        let inner = self.inner.lock().unwrap();
+        let key = inner.key.as_ref().ok_or_else(locked)?;
+        let bytes = decrypt(key, &stored)?;
+        String::from_utf8(bytes).map_err(invalid_utf8)
+    }
+
+    pub fn open_script(&self, mut script: Script) -> AppResult<Script> {
+        script.content = self.open(script.content)?;
+        Ok(script)
+    }
+
+    pub fn unlock(&self, conn: &Connection, passphrase: &str) -> AppResult<()> {
+        let (salt, wrapped): (String, String) = conn
+            .query_row("SELECT salt, wrapped_key FROM vault WHERE id = 1", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .optional()?
+            .ok_or_else(not_enabled)?;
+
+        let kek = derive_key(passphrase, &salt)?;
+        let data_key = decrypt(&kek, &wrapped).map_err(|_| AppError::new(ErrorCode::WrongPassphrase, "Wrong passphrase"))?;
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.key = Some(*Key::from_slice(&data_key));
+        inner.last_activity = Instant::now();
+        Ok(())
+    }
+
+    pub fn lock(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.enabled {
+            inner.key = None;
+        }
+    }
+
+    /// Encrypt every script with a new data key protected by `passphrase`.
+    pub fn enable(&self, conn: &mut Connection, passphrase: &str) -> AppResult<()> {
+        if self.status().enabled {
+            return Err(AppError::new(ErrorCode::Encryption, "Encryption is already enabled"));
+        }
+        check_passphrase(passphrase)?;
+
+        let data_key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        let (salt, wrapped) = wrap_key(passphrase, &data_key)?;
+
+        let tx = conn.transaction()?;
+        rewrite_content(&tx, |content| {
+            if content.starts_with(PREFIX) {
+                Ok(content)
+            } else {
+                encrypt(&data_key, content.as_bytes())
+            }
+        })?;
+        tx.execute(
+            "INSERT INTO vault (id, salt, wrapped_key, auto_lock_minutes) VALUES (1, ?1, ?2, NULL)",
+            rusqlite::params![salt, wrapped],
+        )?;
+        tx.commit()?;
+        // Rebuild the file so freed pages don't keep the old plaintext
+        conn.execute_batch("VACUUM")?;
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.enabled = true;
+        inner.key = Some(data_key);
+        inner.auto_lock = None;
+        inner.last_activity = Instant::now();
+        Ok(())
+    }
+
+    /// Decrypt every script and forget the key.
+    pub fn disable(&self, conn: &mut Connection, passphrase: &str) -> AppResult<()> {
+        self.unlock(conn, passphrase)?;
+        let key = self.inner.lock().unwrap().key.ok_or_else(locked)?;
+
+        let tx = conn.transaction()?;
+        rewrite_content(&tx, |content| {
+            if content.starts_with(PREFIX) {
+                String::from_utf8(decrypt(&key, &content)?).map_err(invalid_utf8)
+            } else {
+                Ok(content)
+            }
+        })?;
+        tx.execute("DELETE FROM vault", [])?;
+        tx.commit()?;
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.enabled = false;
+        inner.key = None;
+        inner.auto_lock = None;
+        Ok(())
+    }
+
+    pub fn change_passphrase(&self, conn: &Connection, current: &str, new: &str) -> AppResult<()> {
+        check_passphrase(new)?;
+        self.unlock(conn, current)?;
+        let key = self.inner.lock().unwrap().key.ok_or_else(locked)?;
+
+        let (salt, wrapped) = wrap_key(new, &key)?;
+        conn.execute(
+            "UPDATE vault SET salt = ?1, wrapped_key = ?2 WHERE id = 1",
+            rusqlite::params![salt, wrapped],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_auto_lock(&self, conn: &Connection, minutes: Option<u32>) -> AppResult<()> {
+        if !self.status().enabled {
+            return Err(not_enabled());
+        }
+        let minutes = minutes.filter(|m| *m > 0);
+        conn.execute(
+            "UPDATE vault SET auto_lock_minutes = ?1 WHERE id = 1",
+            rusqlite::params![minutes],
+        )?;
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.auto_lock = minutes.map(|m| Duration::from_secs(m as u64 * 60));
+        inner.last_activity = Instant::now();
+        Ok(())
+    }
+
+    /// Record user activity, postponing auto-lock.
+    pub fn touch(&self) {
+        self.inner.lock().unwrap().last_activity = Instant::now();
+    }
+
+    /// Whether the library is unlocked and has been idle past the auto-lock limit.
+    pub fn idle_expired(&self) -> bool {
+        let inner = self.inner.lock().unwrap();
+        match (inner.auto_lock, &inner.key) {
+            (Some(limit), Some(_)) => inner.last_activity.elapsed() >= limit,
+            _ => false,
+        }
+    }
+}
+
+/// Show the main window, or the unlock window while the library is locked.
+pub fn show_main(app: &AppHandle) {
+    let locked = app
+        .try_state::<AppState>()
+        .is_some_and(|state| state.db.vault.is_locked());
+    if locked {
+        prompt_unlock(app).ok();
+    } else if let Some(w) = app.get_webview_window("main") {
+        w.show().ok();
+        w.set_focus().ok();
+    }
+}
+
+/// Hide the main window and ask for the passphrase.
+pub fn prompt_unlock(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(w) = app.get_webview_window("main") {
+        w.hide().ok();
+    }
+    if let Some(w) = app.get_webview_window("unlock") {
+        w.show().ok();
+        w.set_focus().ok();
+        return Ok(());
+    }
+
+    WebviewWindowBuilder::new(app, "unlock", WebviewUrl::App("unlock.html".into()))
+        .title("Whispr — Locked")
+        .inner_size(400.0, 240.0)
+        .center()
+        .resizable(false)
+        .build()?;
+    Ok(())
+}
+
+/// Forget the key and clear every window that may be showing script content.
+pub fn lock_now(app: &AppHandle) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    state.db.vault.lock();
+
+    // Also refreshes the tray
+    crate::commands::window::close_prompter(app.clone()).ok();
+    app.emit("library-locked", ()).ok();
+    prompt_unlock(app).ok();
+}
+
+/// Called after a successful unlock: bring the library back on screen.
+pub fn finish_unlock(app: &AppHandle) {
+    if let Some(state) = app.try_state::<AppState>() {
+        state.folder_sync.request_sync();
+    }
+    crate::tray::refresh(app);
+
+    if let Some(w) = app.get_webview_window("main") {
+        // The main window dropped its scripts when the library locked
+        w.eval("window.location.reload()").ok();
+        w.show().ok();
+        w.set_focus().ok();
+    }
+    if let Some(w) = app.get_webview_window("unlock") {
+        w.close().ok();
+    }
+}
+
+/// Lock the library once it has been idle past the auto-lock limit.
+/// A running prompter counts as activity.
+pub fn watch_idle(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(Duration::from_secs(15));
+        let Some(state) = app.try_state::<AppState>() else {
+            continue;
+        };
+        if PROMPTER_WINDOWS
+            .iter()
+            .any(|label| app.get_webview_window(label).is_some())
+        {
+            state.db.vault.touch();
+        } else if state.db.vault.idle_expired() {
+            lock_now(&app);
+        }
+    });
+}
+
+fn locked() -> AppError {
+    AppError::new(ErrorCode::LibraryLocked, "The library is locked")
+}
+
+fn not_enabled() -> AppError {
+    AppError::new(ErrorCode::Encryption, "Encryption is not enabled")
+}
+
+fn invalid_utf8(e: std::string::FromUtf8Error) -> AppError {
+    AppError::new(ErrorCode::Encryption, "Decrypted content is not valid text").with_details(e.to_string())
+}
+
+fn invalid_base64(e: base64::DecodeError) -> AppError {
+    AppError::new(ErrorCode::Encryption, "Encrypted value is corrupt").with_details(e.to_string())
+}
+
+fn check_passphrase(passphrase: &str) -> AppResult<()> {
+    if passphrase.chars().count() < 8 {
+        return Err(AppError::new(ErrorCode::Encryption, "Passphrase must be at least 8 characters"));
+    }
+    Ok(())
+}
+
+fn derive_key(passphrase: &str, salt: &str) -> AppResult<Key> {
+    let salt = BASE64.decode(salt).map_err(invalid_base64)?;
+    let mut key = Key::default();
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+        .map_err(|e| AppError::new(ErrorCode::Encryption, e.to_string()))?;
+    Ok(key)
+}
+
+/// Returns the new salt and the data key encrypted under the passphrase key.
+fn wrap_key(passphrase: &str, data_key: &Key) -> AppResult<(String, String)> {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let salt = BASE64.encode(salt);
+    let kek = derive_key(passphrase, &salt)?;
+    Ok((salt, encrypt(&kek, data_key)?))
+}
+
+fn encrypt(key: &Key, plaintext: &[u8]) -> AppResult<String> {
+    let cipher = ChaCha20Poly1305::new(key);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| AppError::new(ErrorCode::Encryption, "Encryption failed"))?;
+
+    let mut bytes = nonce.to_vec();
+    bytes.extend_from_slice(&ciphertext);
+    Ok(format!("{}{}", PREFIX, BASE64.encode(bytes)))
+}
+
+fn decrypt(key: &Key, stored: &str) -> AppResult<Vec<u8>> {
+    let encoded = stored.strip_prefix(PREFIX).ok_or_else(|| AppError::new(ErrorCode::Encryption, "Value is not encrypted"))?;
+    let bytes = BASE64.decode(encoded).map_err(invalid_base64)?;
+    if bytes.len() < NONCE_LEN {
+        return Err(AppError::new(ErrorCode::Encryption, "Encrypted value is truncated"));
+    }
+    let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+    ChaCha20Poly1305::new(key)
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| AppError::new(ErrorCode::Encryption, "Could not decrypt script content"))
+}
+
+fn rewrite_content(
+    tx: &rusqlite::Transaction,
+    transform: impl Fn(String) -> AppResult<String>,
+) -> AppResult<()> {
+    // Variable values are filled into scripts, so they're kept like content
+    for (table, column) in [("scripts", "content"), ("script_variables", "value")] {
+        let rows = {
+            let mut stmt = tx.prepare(&format!("SELECT rowid, {} FROM {}", column, table))?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows
+        };
+
+        for (rowid, value) in rows {
+            tx.execute(
+                &format!("UPDATE {} SET {} = ?1 WHERE rowid = ?2", table, column),
+                rusqlite::params![transform(value)?, rowid],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSPHRASE: &str = "correct horse";
+
+    fn library() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::apply_migrations(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO scripts (id, title, content, created_at, updated_at) VALUES ('a', 'Intro', 'Hello', '', '');
+             INSERT INTO script_variables (script_id, name, value) VALUES ('a', 'Name', 'Sam');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn stored(conn: &Connection) -> (String, String) {
+        conn.query_row(
+            "SELECT s.content, v.value FROM scripts s JOIN script_variables v ON v.script_id = s.id",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap()
+    }
+
+    fn code(result: AppResult<impl std::fmt::Debug>) -> ErrorCode {
+        result.unwrap_err().code
+    }
+
+    #[test]
+    fn sealed_values_open_to_the_same_text() {
+        let mut conn = library();
+        let vault = Vault::disabled();
+        assert_eq!(vault.seal("Hello").unwrap(), "Hello");
+
+        vault.enable(&mut conn, PASSPHRASE).unwrap();
+        let sealed = vault.seal("Hello").unwrap();
+        assert!(sealed.starts_with(PREFIX));
+        assert!(!sealed.contains("Hello"));
+        // A fresh nonce each time
+        assert_ne!(vault.seal("Hello").unwrap(), sealed);
+        assert_eq!(vault.open(sealed.clone()).unwrap(), "Hello");
+        assert_eq!(vault.open("plain".into()).unwrap(), "plain");
+
+        vault.lock();
+        assert_eq!(code(vault.open(sealed)), ErrorCode::LibraryLocked);
+        assert_eq!(code(vault.seal("Hello")), ErrorCode::LibraryLocked);
+    }
+
+    #[test]
+    fn wrapped_keys_open_only_with_their_passphrase() {
+        let data_key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        let (salt, wrapped) = wrap_key(PASSPHRASE, &data_key).unwrap();
+
+        let unwrapped = decrypt(&derive_key(PASSPHRASE, &salt).unwrap(), &wrapped).unwrap();
+        assert_eq!(unwrapped, data_key.to_vec());
+        assert!(decrypt(&derive_key("battery staple", &salt).unwrap(), &wrapped).is_err());
+    }
+
+    #[test]
+    fn enabling_then_disabling_restores_the_plaintext() {
+        let mut conn = library();
+        let vault = Vault::disabled();
+        assert_eq!(code(vault.enable(&mut conn, "short")), ErrorCode::Encryption);
+        vault.enable(&mut conn, PASSPHRASE).unwrap();
+
+        let (content, value) = stored(&conn);
+        assert!(content.starts_with(PREFIX) && value.starts_with(PREFIX));
+
+        // Reopened, the library starts locked
+        let reopened = Vault::load(&conn).unwrap();
+        assert!(reopened.is_locked());
+        assert_eq!(code(reopened.disable(&mut conn, "battery staple")), ErrorCode::WrongPassphrase);
+        reopened.disable(&mut conn, PASSPHRASE).unwrap();
+
+        assert_eq!(stored(&conn), ("Hello".to_string(), "Sam".to_string()));
+        assert!(!reopened.status().enabled);
+        assert!(!Vault::load(&conn).unwrap().status().enabled);
+    }
+
+    #[test]
+    fn changing_the_passphrase_keeps_the_content() {
+        let mut conn = library();
+        let vault = Vault::disabled();
+        vault.enable(&mut conn, PASSPHRASE).unwrap();
+
+        let new = "battery staple";
+        assert_eq!(code(vault.change_passphrase(&conn, "wrong passphrase", new)), ErrorCode::WrongPassphrase);
+        assert_eq!(code(vault.change_passphrase(&conn, PASSPHRASE, "short")), ErrorCode::Encryption);
+        vault.change_passphrase(&conn, PASSPHRASE, new).unwrap();
+
+        let reopened = Vault::load(&conn).unwrap();
+        assert_eq!(code(reopened.unlock(&conn, PASSPHRASE)), ErrorCode::WrongPassphrase);
+        reopened.unlock(&conn, new).unwrap();
+        assert_eq!(reopened.open(stored(&conn).0).unwrap(), "Hello");
+    }
+}
@@ -7,6 +7,7 @@ const App = {
 
   async init() {
     Editor.init();
+    RunLists.init();
 
     // Load settings to get theme
     try {
@@ -49,13 +50,14 @@ const App = {
 
     list.innerHTML = filtered
       .map(s => {
-        const words = Utils.wordCount(s.content);
+        const words = s.word_count ?? Utils.wordCount(s.content);
+        const duration = s.estimated_seconds != null ? ` &middot; ${Utils.formatTime(s.estimated_seconds)}` : '';
         const date = Utils.formatDate(s.updated_at);
         const preview = Utils.stripMarkdown(s.content).substring(0, 60).trim();
         return `
       <div class="script-item ${s.id === this.selectedId ? 'active' : ''}" data-id="${s.id}">
         <div class="script-item-title">${Utils.escapeHtml(s.title)}</div>
-        <div class="script-item-meta">${words} words &middot; ${date}</div>
+        <div class="script-item-meta">${words} words${duration} &middot; ${date}</div>
         ${preview ? `<div class="script-item-preview">${Utils.escapeHtml(preview)}</div>` : ''}
       </div>`;
       })
@@ -127,19 +129,102 @@ const App = {
 
   async startPrompter(modeOverride) {
     if (!Editor.currentScriptId) return;
-    await Editor.save();
 
     try {
+      if (!(await Editor.save())) return;
+
       const mode = modeOverride || document.getElementById('mode-picker')?.value
         || (await Utils.invoke('get_settings'))?.prompter_mode || 'floating';
 
-      if (mode === 'notch') {
-        await Utils.invoke('open_topbar_prompter', { scriptId: Editor.currentScriptId });
+      await this.openPrompter(Editor.currentScriptId, mode);
+    } catch (e) {
+      if (e?.code === 'script_not_found') {
+        await this.handleDeletedScript();
       } else {
-        await Utils.invoke('open_floating_prompter', { scriptId: Editor.currentScriptId });
+        console.error('Failed to start prompter:', Utils.errorMessage(e));
       }
+    }
+  },
+
+  // Opens the prompter, first asking for any placeholder values it's missing
+  async openPrompter(scriptId, mode, allowUnresolved = false) {
+    const command = mode === 'notch' ? 'open_topbar_prompter' : 'open_floating_prompter';
+    try {
+      await Utils.invoke(command, { scriptId, allowUnresolved });
     } catch (e) {
-      console.error('Failed to start prompter:', e);
+      if (e?.code !== 'unresolved_placeholders') throw e;
+      const choice = await this.askForVariables(scriptId);
+      if (!choice) return;
+      await Utils.invoke('set_script_variables', { scriptId, values: choice.values });
+      await this.openPrompter(scriptId, mode, choice.anyway);
+    }
+  },
+
+  // Resolves to { values, anyway } or null when cancelled
+  async askForVariables(scriptId) {
+    const variables = await Utils.invoke('get_script_variables', { scriptId });
+    const modal = document.getElementById('variables-modal');
+    const form = document.getElementById('variables-form');
+    const fields = document.getElementById('variables-fields');
+
+    // Built with the DOM so names and values need no attribute escaping
+    fields.replaceChildren(...variables.map(v => {
+      const label = document.createElement('label');
+      const input = document.createElement('input');
+      label.textContent = `[${v.name}]`;
+      input.type = 'text';
+      input.className = 'text-input';
+      input.dataset.name = v.name;
+      input.value = v.value || '';
+      label.append(input);
+      return label;
+    }));
+    modal.style.display = 'flex';
+    const inputs = [...fields.querySelectorAll('input')];
+    (inputs.find(input => !input.value) || inputs[0])?.focus();
+
+    return new Promise(resolve => {
+      const finish = (anyway, cancelled) => {
+        modal.style.display = 'none';
+        form.onsubmit = null;
+        document.getElementById('btn-variables-anyway').onclick = null;
+        document.getElementById('btn-variables-cancel').onclick = null;
+        if (cancelled) return resolve(null);
+        const values = {};
+        fields.querySelectorAll('input').forEach(input => {
+          values[input.dataset.name] = input.value.trim();
+        });
+        resolve({ values, anyway });
+      };
+      form.onsubmit = (e) => {
+        e.preventDefault();
+        finish(false, false);
+      };
+      document.getElementById('btn-variables-anyway').onclick = () => finish(true, false);
+      document.getElementById('btn-variables-cancel').onclick = () => finish(false, true);
+    });
+  },
+
+  // The open script was deleted elsewhere (sync, another window)
+  async handleDeletedScript() {
+    if (this.handlingDeleted) return;
+    this.handlingDeleted = true;
+    try {
+      const title = Editor.getTitle() || 'Untitled Script';
+      const content = Editor.getContent();
+      const keep = confirm(`"${title}" was deleted. Keep your text as a new script?`);
+      Editor.clear();
+      this.selectedId = null;
+
+      if (keep) {
+        const script = await ScriptManager.create(title, content);
+        await this.loadScripts();
+        if (script) this.selectScript(script.id);
+      } else {
+        await this.loadScripts();
+      }
+    } finally {
+      this.handlingDeleted = false;
     }
   },
 
@@ -295,17 +380,55 @@ const App = {
       if (id) this.selectScript(id);
     });
 
+    // Library changed outside the editor (folder sync)
+    Utils.listen('library-changed', async () => {
+      await this.loadScripts();
+      const current = this.scripts.find(s => s.id === this.selectedId);
+      if (!current) {
+        if (this.selectedId) {
+          Editor.clear();
+          this.selectedId = null;
+        }
+      } else if (current.content !== Editor.getContent() || current.title !== Editor.getTitle()) {
+        Editor.load(current);
+      }
+    });
+
+    // Library locked — drop everything decrypted; the page reloads on unlock
+    Utils.listen('library-locked', () => {
+      this.scripts = [];
+      this.selectedId = null;
+      Editor.clear();
+      this.renderScriptList();
+    });
+
+    // A tray start that needs placeholder values filled in
+    Utils.listen('prompt-variables', async (event) => {
+      const { scriptId, mode } = event.payload || {};
+      if (!scriptId) return;
+      this.selectScript(scriptId);
+      try {
+        await this.openPrompter(scriptId, mode);
+      } catch (e) {
+        console.error('Failed to start prompter:', Utils.errorMessage(e));
+      }
+    });
+
+    // A cue output that didn't go through while prompting
+    Utils.listen('cue-output-failed', (event) => {
+      const { output_name, cue, message } = event.payload || {};
+      console.error(`Cue ${cue} → ${output_name} failed:`, message);
+    });
+
     // Global shortcut: toggle prompter
     Utils.listen('global-toggle-prompter', () => {
       if (this.selectedId) this.startPrompter();
     });
 
     // Settings changed — refresh theme
-    Utils.listen('settings-changed', async () => {
-      try {
-        const settings = await Utils.invoke('get_settings');
-        if (settings) ThemeManager.set(settings.appearance_mode);
-      } catch (e) {}
+    Utils.listen('settings-changed', (event) => {
+      const settings = event.payload?.settings;
+      if (settings) ThemeManager.set(settings.appearance_mode);
     });
   },
 };
@@ -10,16 +10,24 @@ const Editor = {
     this.wordCountEl = document.getElementById('word-count');
     this.charCountEl = document.getElementById('char-count');
     this.readTimeEl = document.getElementById('read-time');
+    this.markupIssuesEl = document.getElementById('markup-issues');
+    this.targetInput = document.getElementById('target-duration');
+    this.sessionStatsEl = document.getElementById('session-stats');
     this.editorView = document.getElementById('editor-view');
     this.emptyState = document.getElementById('empty-state');
 
     // Auto-save on typing
     const debouncedSave = Utils.debounce(() => this.save(), 800);
+    const debouncedCheck = Utils.debounce(() => this.checkMarkup(), 400);
     this.titleInput.addEventListener('input', debouncedSave);
     this.textarea.addEventListener('input', () => {
       this.updateStats();
       debouncedSave();
+      debouncedCheck();
     });
+    this.targetInput.addEventListener('change', () => this.saveTarget());
+    // Back from prompting: the take just finished is in the stats now
+    window.addEventListener('focus', () => this.showSessions());
   },
 
   load(script) {
@@ -27,6 +35,11 @@ const Editor = {
     this.titleInput.value = script.title;
     this.textarea.value = script.content;
     this.updateStats();
+    this.checkMarkup();
+    this.analyze();
+    this.targetInput.value = '';
+    this.showTarget();
+    this.showSessions();
     this.showEditor();
   },
 
@@ -35,6 +48,9 @@ const Editor = {
     this.titleInput.value = '';
     this.textarea.value = '';
     this.updateStats();
+    this.markupIssuesEl.textContent = '';
+    this.targetInput.value = '';
+    this.sessionStatsEl.textContent = '';
     this.showEmpty();
   },
 
@@ -56,13 +72,133 @@ const Editor = {
     this.readTimeEl.textContent = Utils.readTime(text);
   },
 
+  // Markup problems as the prompter would see them, listed in the tooltip
+  async checkMarkup() {
+    try {
+      const doc = await Utils.invoke('parse_script', { content: this.textarea.value });
+      const issues = doc?.diagnostics || [];
+      this.markupIssuesEl.textContent = issues.length
+        ? `${issues.length} markup issue${issues.length !== 1 ? 's' : ''}`
+        : '';
+      this.markupIssuesEl.title = issues
+        .map(d => `Line ${d.line}, column ${d.column}: ${d.message}`)
+        .join('\n');
+    } catch (e) {
+      this.markupIssuesEl.textContent = '';
+    }
+  },
+
+  // Duration at the configured speed, with per-section pacing in the tooltip
+  async analyze() {
+    const id = this.currentScriptId;
+    if (!id) return;
+    try {
+      const analysis = await Utils.invoke('analyze_script', { scriptId: id });
+      if (id !== this.currentScriptId) return;
+      this.readTimeEl.textContent = `${Utils.formatTime(analysis.estimated_seconds)} at ${Math.round(analysis.wpm)} wpm`;
+      this.readTimeEl.title = analysis.sections
+        .map(s => {
+          const pace = s.pause_seconds > 0 ? `, read at ${Math.round(s.target_wpm)} wpm` : '';
+          return `${s.title || 'Opening'}: ${s.word_count} words, ${Utils.formatTime(s.estimated_seconds)}${pace}`;
+        })
+        .join('\n');
+    } catch (e) {
+      this.readTimeEl.title = '';
+    }
+  },
+
+  async saveTarget() {
+    const id = this.currentScriptId;
+    if (!id) return;
+    const text = this.targetInput.value.trim();
+    const seconds = text ? Utils.parseDuration(text) : null;
+    if (text && !seconds) {
+      this.targetInput.classList.add('invalid');
+      this.targetInput.title = 'Enter a duration like 2:30';
+      return;
+    }
+    try {
+      await Utils.invoke('set_target_duration', { scriptId: id, seconds });
+      await this.showTarget();
+    } catch (e) {
+      this.targetInput.classList.add('invalid');
+      this.targetInput.title = Utils.errorMessage(e);
+    }
+  },
+
+  // The target duration and the speed it needs, kept current as the script changes
+  async showTarget() {
+    const id = this.currentScriptId;
+    if (!id) return;
+    try {
+      const target = await Utils.invoke('compute_target_speed', { scriptId: id });
+      if (id !== this.currentScriptId) return;
+      this.targetInput.classList.toggle('invalid', !!target?.clamped);
+      if (!target) {
+        this.targetInput.title = 'Scroll through the script in exactly this long, countdown excluded';
+        return;
+      }
+      if (document.activeElement !== this.targetInput) {
+        this.targetInput.value = Utils.formatTime(target.target_seconds);
+      }
+      this.targetInput.title = target.clamped
+        ? `Can't fit: at ${Math.round(target.wpm)} wpm it takes ${Utils.formatTime(target.estimated_seconds)}`
+        : `Prompts at ${Math.round(target.wpm)} wpm`;
+    } catch (e) {
+      this.targetInput.title = '';
+    }
+  },
+
+  // Rehearsal history: takes so far, average length and which way it's going
+  async showSessions() {
+    const id = this.currentScriptId;
+    if (!id) return;
+    try {
+      const stats = await Utils.invoke('get_session_stats', { scriptId: id });
+      if (id !== this.currentScriptId) return;
+      if (!stats.sessions) {
+        this.sessionStatsEl.textContent = '';
+        return;
+      }
+      let text = `${stats.sessions} take${stats.sessions !== 1 ? 's' : ''}`;
+      if (stats.average_duration_seconds != null) {
+        text += `, avg ${Utils.formatTime(stats.average_duration_seconds)}`;
+      }
+      if (stats.duration_trend != null && Math.abs(stats.duration_trend) >= 1) {
+        text += stats.duration_trend < 0 ? ' ↓' : ' ↑';
+      }
+      this.sessionStatsEl.textContent = text;
+      const lines = [
+        `${stats.completed} run-through${stats.completed !== 1 ? 's' : ''} to the end`,
+      ];
+      if (stats.average_wpm != null) lines.push(`Average ${Math.round(stats.average_wpm)} wpm`);
+      if (stats.average_pauses != null) lines.push(`Average ${stats.average_pauses.toFixed(1)} pauses`);
+      if (stats.duration_trend != null) {
+        const change = Math.round(Math.abs(stats.duration_trend));
+        lines.push(`${change}s ${stats.duration_trend < 0 ? 'shorter' : 'longer'} each run-through lately`);
+      }
+      this.sessionStatsEl.title = lines.join('\n');
+    } catch (e) {
+      this.sessionStatsEl.textContent = '';
+    }
+  },
+
   async save() {
-    if (!this.currentScriptId) return;
-    await ScriptManager.update(
-      this.currentScriptId,
-      this.titleInput.value || 'Untitled Script',
-      this.textarea.value
-    );
+    if (!this.currentScriptId) return true;
+    try {
+      await ScriptManager.update(
+        this.currentScriptId,
+        this.titleInput.value || 'Untitled Script',
+        this.textarea.value
+      );
+      this.analyze();
+      this.showTarget();
+      return true;
+    } catch (e) {
+      if (e?.code !== 'script_not_found') throw e;
+      await App.handleDeletedScript();
+      return false;
+    }
   },
 
   getContent() {
@@ -13,6 +13,8 @@ class PrompterEngine {
     this.isPaused = false;
     this.isReversed = false;
     this.elapsedSeconds = 0;
+    // Last word whose cues have been reported
+    this.cuesReached = -1;
 
     // Time tracking (matches Swift's scrollStartDate pattern)
     this.scrollStartDate = null;
@@ -39,6 +41,7 @@ class PrompterEngine {
     this.onEnd = options.onEnd || null;
     this.onPauseChange = options.onPauseChange || null;
     this.onSpeedChange = options.onSpeedChange || null;
+    this.onCue = options.onCue || null;
 
     // Internal (30fps timer matching Swift's 1/30 interval)
     this._timer = null;
@@ -55,6 +58,70 @@ class PrompterEngine {
     this._recalculateSpeed();
   }
 
+  /**
+   * Load a parsed script (see markup.rs). Cues and section titles ride on
+   * the word that follows them, so they take no reading time; each
+   * paragraph counts as one line. Words are counted as analysis.rs counts
+   * them, so its duration estimates match the scroll. Each cue keeps its
+   * position among the document's cues, which is how it's reported.
+   */
+  loadDocument(doc) {
+    const words = [];
+    const lines = [];
+    let cues = [];
+    let cueCount = 0;
+    let section = null;
+
+    for (const s of doc?.sections || []) {
+      if (s.title) section = s.title;
+      for (const paragraph of s.paragraphs) {
+        const start = words.length;
+        for (const inline of paragraph.inlines) {
+          if (inline.type === 'cue') {
+            cues.push({ ...inline, index: cueCount++ });
+            continue;
+          }
+          // A CJK sentence is one token but many words; `joined` words
+          // are shown without a space after them
+          const pieces = inline.type === 'placeholder'
+            ? [{ text: `[${inline.name}]`, joined: false }]
+            : inline.text.split(/\s+/).filter(Boolean).flatMap(token => {
+              const parts = Utils.wordPieces(token);
+              return parts.map((text, i) => ({ text, joined: i < parts.length - 1 }));
+            });
+          for (const { text, joined } of pieces) {
+            words.push({
+              text,
+              joined,
+              index: words.length,
+              lower: text.toLowerCase().replace(/[^\w]/g, ''),
+              strong: !!inline.strong,
+              em: !!inline.em,
+              placeholder: inline.type === 'placeholder',
+              section,
+              cues,
+            });
+            cues = [];
+            section = null;
+          }
+        }
+        if (words.length > start) {
+          lines.push(words.slice(start).map(w => w.text).join(' '));
+        }
+      }
+    }
+    // Cues after the last word still show, on that word
+    if (cues.length && words.length) words[words.length - 1].cues.push(...cues);
+
+    this.words = words;
+    this.lines = lines;
+    this.currentWordIndex = 0;
+    this.currentLineIndex = 0;
+    this.scrollOffset = 0;
+    this.cuesReached = -1;
+    this._recalculateSpeed();
+  }
+
   /**
    * Recalculate speed — exact port of Swift's recalculateSpeed()
    * displaySpeed = scrollSpeed * 3.0 (WPM)
@@ -139,6 +206,7 @@ class PrompterEngine {
       if (this.onWordChange) this.onWordChange(this.currentWordIndex);
     }
 
+    this._reachCues();
     if (this.onScroll) this.onScroll(this.scrollOffset);
 
     // Check end of script
@@ -147,6 +215,28 @@ class PrompterEngine {
     }
   }
 
+  /**
+   * Report the cues of every word scrolled onto since the last tick. Only
+   * forward scrolling fires cues, each once per pass.
+   */
+  _reachCues() {
+    if (this.isReversed) return;
+    for (let i = this.cuesReached + 1; i <= this.currentWordIndex; i++) {
+      for (const cue of this.words[i].cues || []) {
+        if (this.onCue) this.onCue(cue);
+      }
+    }
+    this.cuesReached = Math.max(this.cuesReached, this.currentWordIndex);
+  }
+
+  /**
+   * After a jump or restart: cues from the new position on fire again,
+   * cues jumped over don't
+   */
+  _rearmCues() {
+    this.cuesReached = this.currentWordIndex - 1;
+  }
+
   _handleEnd() {
     switch (this.endAction) {
       case 'loop':
@@ -157,16 +247,19 @@ class PrompterEngine {
         this.scrollStartWordIndex = 0;
         this.scrollStartPixelOffset = 0;
         this.totalPauseTime = 0;
+        this._rearmCues();
         if (this.onWordChange) this.onWordChange(0);
         if (this.onScroll) this.onScroll(0);
         break;
+      // onEnd first, so the window can report finishing before the pause
+      // or stop it would otherwise report
       case 'close':
-        this.stop();
         if (this.onEnd) this.onEnd('close');
+        this.stop();
         break;
       default:
-        this.pause();
         if (this.onEnd) this.onEnd('stop');
+        this.pause();
         break;
     }
   }
@@ -209,24 +302,51 @@ class PrompterEngine {
   }
 
   increaseSpeed(amount = 10) {
-    this.scrollSpeed = Math.min(200, this.scrollSpeed + amount);
-    // Reset anchor before recalculating
-    this.scrollStartDate = Date.now();
-    this.scrollStartWordIndex = this.currentWordIndex;
-    this.scrollStartPixelOffset = this.scrollOffset;
-    this.totalPauseTime = 0;
+    this.setSpeed(this.scrollSpeed + amount);
+  }
+
+  decreaseSpeed(amount = 10) {
+    this.setSpeed(this.scrollSpeed - amount);
+  }
+
+  setSpeed(speed) {
+    this.scrollSpeed = Math.max(10, Math.min(200, speed));
+    this._resetAnchor();
     this._recalculateSpeed();
     if (this.onSpeedChange) this.onSpeedChange(this.scrollSpeed);
   }
 
-  decreaseSpeed(amount = 10) {
-    this.scrollSpeed = Math.max(10, this.scrollSpeed - amount);
+  /**
+   * Font size changes the line height, and with it the pixel speed
+   */
+  setFontSize(size) {
+    this.fontSize = size;
+    this._resetAnchor();
+    this._recalculateSpeed();
+  }
+
+  /**
+   * Back to the first word, keeping the running and paused state
+   */
+  restart() {
+    this.currentWordIndex = 0;
+    this.currentLineIndex = 0;
+    this.scrollOffset = 0;
+    this.elapsedSeconds = 0;
+    this._resetAnchor();
+    this._rearmCues();
+    if (this.pauseStartTime) this.pauseStartTime = Date.now();
+    if (this.onWordChange) this.onWordChange(0);
+    if (this.onScroll) this.onScroll(0);
+    if (this.onTimerUpdate) this.onTimerUpdate(0);
+  }
+
+  // Reset anchor before recalculating
+  _resetAnchor() {
     this.scrollStartDate = Date.now();
     this.scrollStartWordIndex = this.currentWordIndex;
     this.scrollStartPixelOffset = this.scrollOffset;
     this.totalPauseTime = 0;
-    this._recalculateSpeed();
-    if (this.onSpeedChange) this.onSpeedChange(this.scrollSpeed);
   }
 
   jumpForward() {
@@ -244,6 +364,7 @@ class PrompterEngine {
     this.scrollStartWordIndex = this.currentWordIndex;
     this.scrollStartPixelOffset = this.scrollOffset;
     this.totalPauseTime = 0;
+    this._rearmCues();
     if (this.onWordChange) this.onWordChange(this.currentWordIndex);
     if (this.onScroll) this.onScroll(this.scrollOffset);
   }
@@ -266,6 +387,7 @@ class PrompterEngine {
     this.scrollStartWordIndex = this.currentWordIndex;
     this.scrollStartPixelOffset = this.scrollOffset;
     this.totalPauseTime = 0;
+    this._rearmCues();
     if (this.onWordChange) this.onWordChange(this.currentWordIndex);
     if (this.onScroll) this.onScroll(this.scrollOffset);
   }
@@ -309,6 +431,7 @@ class PrompterEngine {
       this.scrollStartWordIndex = index;
       this.scrollStartPixelOffset = this.scrollOffset;
       this.totalPauseTime = 0;
+      this._rearmCues();
       if (this.onWordChange) this.onWordChange(this.currentWordIndex);
       if (this.onScroll) this.onScroll(this.scrollOffset);
     }
@@ -7,34 +7,21 @@ const FloatingPrompter = {
   settings: null,
   scriptId: null,
   isHoverPaused: false,
+  isHeld: false,
+  isFinished: false,
+  reportedPercent: null,
 
   async init() {
     this.settings = await Utils.invoke('get_settings');
     if (!this.settings) this.settings = {};
 
-    // Apply theme
-    document.documentElement.setAttribute('data-theme', this.settings.floating_theme || 'dark');
-
-    // Apply font
-    const textEl = document.getElementById('floating-text');
-    textEl.style.fontSize = `${this.settings.floating_font_size || 32}px`;
-    textEl.style.fontFamily = Utils.fontFamilyCSS(this.settings.floating_font_family);
-
-    // Apply text color
-    if (this.settings.text_color_hex && this.settings.text_color_hex !== '#FFFFFF') {
-      textEl.style.color = this.settings.text_color_hex;
-    }
-
-    // Mirror mode
-    if (this.settings.mirror_mode) {
-      document.getElementById('floating-container').classList.add('mirrored');
-    }
+    this.applySettings(this.settings);
 
     // Listen for script from Rust backend
     Utils.listen('load-script', (event) => {
       const data = event.payload;
       this.scriptId = data.id;
-      this.startWithCountdown(data.content);
+      this.startWithCountdown(data.document, data.speed, data.end_action);
     });
 
     // Global pause shortcut
@@ -44,16 +31,37 @@ const FloatingPrompter = {
       }
     });
 
+    // Hold to scroll: the global hold key is the only thing that starts scrolling
+    Utils.listen('prompter-hold-start', () => {
+      this.isHeld = true;
+      if (this.settings.hold_to_scroll && this.engine?.isRunning) this.engine.resume();
+    });
+    Utils.listen('prompter-hold-stop', () => {
+      this.isHeld = false;
+      if (this.settings.hold_to_scroll && this.engine?.isRunning) this.engine.pause();
+    });
+
+    // Transport controls from the tray menu
+    Utils.listen('prompter-control', (event) => {
+      if (!this.engine) return;
+      switch (event.payload) {
+        case 'toggle': this.engine.togglePause(); break;
+        case 'faster': this.engine.increaseSpeed(5); break;
+        case 'slower': this.engine.decreaseSpeed(5); break;
+        case 'restart': this.engine.restart(); break;
+      }
+    });
+
     // Settings changed (live update)
     Utils.listen('settings-changed', (event) => {
-      const s = event.payload;
-      if (s) {
-        this.settings = s;
-        document.documentElement.setAttribute('data-theme', s.floating_theme || 'dark');
-        const textEl = document.getElementById('floating-text');
-        textEl.style.fontSize = `${s.floating_font_size || 32}px`;
-        textEl.style.fontFamily = Utils.fontFamilyCSS(s.floating_font_family);
-        if (s.text_color_hex) textEl.style.color = s.text_color_hex;
+      const { settings, changed } = event.payload || {};
+      if (!settings) return;
+      this.settings = settings;
+      this.applySettings(settings);
+      if (this.engine) {
+        if (changed.includes('hold_to_scroll') && settings.hold_to_scroll && !this.isHeld) this.engine.pause();
+        if (changed.includes('floating_scroll_speed')) this.engine.setSpeed(settings.floating_scroll_speed);
+        if (changed.includes('floating_font_size')) this.engine.setFontSize(settings.floating_font_size);
       }
     });
 
@@ -89,7 +97,22 @@ const FloatingPrompter = {
     });
   },
 
-  startWithCountdown(content) {
+  applySettings(s) {
+    document.documentElement.setAttribute('data-theme', s.floating_theme || 'dark');
+
+    const textEl = document.getElementById('floating-text');
+    textEl.style.fontSize = `${s.floating_font_size || 32}px`;
+    textEl.style.fontFamily = Utils.fontFamilyCSS(s.floating_font_family);
+
+    // White is the theme's own text color
+    textEl.style.color = s.text_color_hex && s.text_color_hex !== '#FFFFFF' ? s.text_color_hex : '';
+
+    document.getElementById('floating-container').classList.toggle('mirrored', !!s.mirror_mode);
+  },
+
+  // `speed` meets the script's target duration, or is the configured one;
+  // a run list sets `endAction` so it can move on when the script ends
+  startWithCountdown(doc, speed, endAction) {
     const updateProgress = (elapsed) => {
       document.getElementById('progress-elapsed').textContent = Utils.formatTime(elapsed);
       const remaining = this.engine.getRemainingSeconds();
@@ -99,26 +122,35 @@ const FloatingPrompter = {
     };
 
     this.engine = new PrompterEngine({
-      scrollSpeed: this.settings.floating_scroll_speed || 50,
+      scrollSpeed: speed || this.settings.floating_scroll_speed || 50,
       fontSize: this.settings.floating_font_size || 32,
-      endAction: this.settings.end_action || 'stop',
+      endAction: endAction || this.settings.end_action || 'stop',
       onWordChange: (index) => this.updateWords(index),
       onScroll: (offset) => this.updateScroll(offset),
-      onTimerUpdate: (s) => updateProgress(s),
+      onTimerUpdate: (s) => {
+        updateProgress(s);
+        this.reportProgress();
+      },
       onPauseChange: (paused) => {
         document.getElementById('pause-overlay').classList.toggle('visible', paused);
         const label = document.getElementById('pause-label');
         label.textContent = this.isHoverPaused ? 'HOVER PAUSED' : 'PAUSED';
+        if (!paused) this.isFinished = false;
+        this.reportStatus();
       },
+      onCue: (cue) => this.reportCue(cue),
       onSpeedChange: (speed) => {
         document.getElementById('speed-wpm').textContent = `${Math.round(speed * 3)} WPM`;
+        this.reportStatus();
       },
-      onEnd: (action) => {
+      onEnd: async (action) => {
+        this.isFinished = true;
+        await this.reportStatus();
         if (action === 'close') this.close();
       },
     });
 
-    this.engine.loadScript(content);
+    this.engine.loadDocument(doc);
     this.renderAllWords();
 
     // Keyboard handler
@@ -141,7 +173,7 @@ const FloatingPrompter = {
 
     // Speed display
     document.getElementById('speed-wpm').textContent =
-      `${Math.round((this.settings.floating_scroll_speed || 50) * 3)} WPM`;
+      `${Math.round(this.engine.scrollSpeed * 3)} WPM`;
 
     // Sleep prevention
     Utils.invoke('prevent_sleep', { prevent: true });
@@ -152,6 +184,8 @@ const FloatingPrompter = {
       onComplete: () => {
         this.keyboard.isCountdownActive = false;
         this.engine.start();
+        if (this.settings.hold_to_scroll && !this.isHeld) this.engine.pause();
+        this.reportStatus();
       },
     });
     this.keyboard.isCountdownActive = countdownSeconds > 0;
@@ -162,7 +196,7 @@ const FloatingPrompter = {
     const container = document.getElementById('scroll-content');
     const words = this.engine.words;
     container.innerHTML = words
-      .map((w, i) => `<span class="word upcoming" data-index="${i}">${Utils.escapeHtml(w.text)} </span>`)
+      .map((w, i) => Utils.wordHtml(w, 'word upcoming', ` data-index="${i}"`))
       .join('');
   },
 
@@ -192,6 +226,7 @@ const FloatingPrompter = {
     textEl.addEventListener('mouseleave', () => {
       if (this.isHoverPaused && this.engine?.isPaused) {
         this.isHoverPaused = false;
+        if (this.settings.hold_to_scroll && !this.isHeld) return;
         this.engine.resume();
       }
     });
@@ -211,6 +246,28 @@ const FloatingPrompter = {
     });
   },
 
+  // Lets the tray show the prompter's phase, progress and matching controls
+  reportStatus() {
+    if (!this.engine?.isRunning) return;
+    let phase = this.engine.isPaused ? 'paused' : 'scrolling';
+    if (this.isFinished) phase = 'finished';
+    const progress = this.engine.getProgress();
+    this.reportedPercent = Math.round(progress * 100);
+    return Utils.invoke('report_prompter_status', {
+      phase, progress, speed: this.engine.scrollSpeed,
+    }).catch(() => {});
+  },
+
+  // The backend fires whatever the cue is set up to trigger
+  reportCue(cue) {
+    Utils.invoke('report_cue', { index: cue.index }).catch(() => {});
+  },
+
+  // Progress alone is reported once per whole percent
+  reportProgress() {
+    if (Math.round(this.engine.getProgress() * 100) !== this.reportedPercent) this.reportStatus();
+  },
+
   async close() {
     this.engine?.stop();
     this.keyboard?.destroy();
@@ -6,34 +6,20 @@ const TopbarPrompter = {
   countdown: null,
   settings: null,
   isHoverPaused: false,
+  isHeld: false,
+  isFinished: false,
+  reportedPercent: null,
 
   async init() {
     this.settings = await Utils.invoke('get_settings');
     if (!this.settings) this.settings = {};
 
-    // Apply theme
-    document.documentElement.setAttribute('data-theme', this.settings.appearance_mode || 'dark');
-
-    // Apply font
-    const textEl = document.getElementById('topbar-text');
-    textEl.style.fontSize = `${this.settings.notch_font_size || 20}px`;
-    textEl.style.fontFamily = Utils.fontFamilyCSS(this.settings.notch_font_family);
-
-    // Opacity
-    document.getElementById('topbar-container').style.opacity = this.settings.notch_opacity || 0.92;
-
-    // Glow style
-    const glow = document.getElementById('topbar-glow');
-    glow.className = `topbar-glow ${this.settings.notch_glow_style || 'rainbow'}`;
-
-    // Timer visibility
-    const timer = document.getElementById('topbar-timer');
-    timer.style.display = (this.settings.notch_show_timer !== false) ? 'block' : 'none';
+    this.applySettings(this.settings);
 
     // Listen for script from Rust backend
     Utils.listen('load-script', (event) => {
       const data = event.payload;
-      this.startWithCountdown(data.content);
+      this.startWithCountdown(data.document, data.speed, data.end_action);
     });
 
     // Global pause shortcut
@@ -43,14 +29,38 @@ const TopbarPrompter = {
       }
     });
 
+    // Hold to scroll: the global hold key is the only thing that starts scrolling
+    Utils.listen('prompter-hold-start', () => {
+      this.isHeld = true;
+      if (this.settings.hold_to_scroll && this.engine?.isRunning) this.engine.resume();
+    });
+    Utils.listen('prompter-hold-stop', () => {
+      this.isHeld = false;
+      if (this.settings.hold_to_scroll && this.engine?.isRunning) this.engine.pause();
+    });
+
+    // Transport controls from the tray menu
+    Utils.listen('prompter-control', (event) => {
+      if (!this.engine) return;
+      switch (event.payload) {
+        case 'toggle': this.engine.togglePause(); break;
+        case 'faster': this.engine.increaseSpeed(5); break;
+        case 'slower': this.engine.decreaseSpeed(5); break;
+        case 'restart': this.engine.restart(); break;
+      }
+    });
+
     // Settings changed (live update)
     Utils.listen('settings-changed', (event) => {
-      const s = event.payload;
-      if (s) {
-        this.settings = s;
-        const glow = document.getElementById('topbar-glow');
-        glow.className = `topbar-glow ${s.notch_glow_style || 'rainbow'}`;
-        document.getElementById('topbar-container').style.opacity = s.notch_opacity || 0.92;
+      const { settings, changed } = event.payload || {};
+      if (!settings) return;
+      this.settings = settings;
+      this.applySettings(settings);
+      if (this.engine) {
+        if (changed.includes('hold_to_scroll') && settings.hold_to_scroll && !this.isHeld) this.engine.pause();
+        if (changed.includes('notch_scroll_speed')) this.engine.setSpeed(settings.notch_scroll_speed);
+        if (changed.includes('notch_font_size')) this.engine.setFontSize(settings.notch_font_size);
+        this.renderLine(this.engine.currentWordIndex);
       }
     });
 
@@ -67,23 +77,53 @@ const TopbarPrompter = {
     document.getElementById('btn-close').addEventListener('click', () => this.close());
   },
 
-  startWithCountdown(content) {
+  applySettings(s) {
+    document.documentElement.setAttribute('data-theme', s.appearance_mode || 'dark');
+
+    const textEl = document.getElementById('topbar-text');
+    textEl.style.fontSize = `${s.notch_font_size || 20}px`;
+    textEl.style.fontFamily = Utils.fontFamilyCSS(s.notch_font_family);
+
+    document.getElementById('topbar-container').style.opacity = s.notch_opacity || 0.92;
+
+    const glow = document.getElementById('topbar-glow');
+    glow.className = `topbar-glow ${s.notch_glow_style || 'rainbow'}`;
+
+    const timer = document.getElementById('topbar-timer');
+    timer.style.display = (s.notch_show_timer !== false) ? 'block' : 'none';
+  },
+
+  // `speed` meets the script's target duration, or is the configured one;
+  // a run list sets `endAction` so it can move on when the script ends
+  startWithCountdown(doc, speed, endAction) {
     const timer = document.getElementById('topbar-timer');
 
     this.engine = new PrompterEngine({
-      scrollSpeed: this.settings.notch_scroll_speed || 50,
+      scrollSpeed: speed || this.settings.notch_scroll_speed || 50,
       fontSize: this.settings.notch_font_size || 20,
-      endAction: this.settings.end_action || 'stop',
+      endAction: endAction || this.settings.end_action || 'stop',
       onWordChange: (index) => this.renderLine(index),
-      onTimerUpdate: (s) => { timer.textContent = Utils.formatTime(s); },
+      onTimerUpdate: (s) => {
+        timer.textContent = Utils.formatTime(s);
+        this.reportProgress();
+      },
       onPauseChange: (paused) => {
         const btn = document.getElementById('btn-pause');
         btn.innerHTML = paused ? '&#9654;' : '&#10074;&#10074;';
+        if (!paused) this.isFinished = false;
+        this.reportStatus();
+      },
+      onCue: (cue) => this.reportCue(cue),
+      onSpeedChange: () => this.reportStatus(),
+      onEnd: async () => {
+        this.isFinished = true;
+        await this.reportStatus();
+        // In a run list the next script takes over the window
+        if (!endAction) this.close();
       },
-      onEnd: () => this.close(),
     });
 
-    this.engine.loadScript(content);
+    this.engine.loadDocument(doc);
     this.renderLine(0);
 
     this.keyboard = new KeyboardHandler(this.engine, {
@@ -105,6 +145,8 @@ const TopbarPrompter = {
       onComplete: () => {
         this.keyboard.isCountdownActive = false;
         this.engine.start();
+        if (this.settings.hold_to_scroll && !this.isHeld) this.engine.pause();
+        this.reportStatus();
       },
     });
     this.keyboard.isCountdownActive = countdownSeconds > 0;
@@ -126,7 +168,7 @@ const TopbarPrompter = {
       if (i < wordIndex) cls += ' spoken';
       else if (i === wordIndex) cls += ' current';
       else cls += ' upcoming';
-      html += `<span class="${cls}">${Utils.escapeHtml(words[i].text)} </span>`;
+      html += Utils.wordHtml(words[i], cls);
     }
     line.innerHTML = html;
   },
@@ -142,6 +184,7 @@ const TopbarPrompter = {
     textEl.addEventListener('mouseleave', () => {
       if (this.isHoverPaused && this.engine?.isPaused) {
         this.isHoverPaused = false;
+        if (this.settings.hold_to_scroll && !this.isHeld) return;
         this.engine.resume();
       }
     });
@@ -161,6 +204,28 @@ const TopbarPrompter = {
     });
   },
 
+  // Lets the tray show the prompter's phase, progress and matching controls
+  reportStatus() {
+    if (!this.engine?.isRunning) return;
+    let phase = this.engine.isPaused ? 'paused' : 'scrolling';
+    if (this.isFinished) phase = 'finished';
+    const progress = this.engine.getProgress();
+    this.reportedPercent = Math.round(progress * 100);
+    return Utils.invoke('report_prompter_status', {
+      phase, progress, speed: this.engine.scrollSpeed,
+    }).catch(() => {});
+  },
+
+  // The backend fires whatever the cue is set up to trigger
+  reportCue(cue) {
+    Utils.invoke('report_cue', { index: cue.index }).catch(() => {});
+  },
+
+  // Progress alone is reported once per whole percent
+  reportProgress() {
+    if (Math.round(this.engine.getProgress() * 100) !== this.reportedPercent) this.reportStatus();
+  },
+
   async close() {
     this.engine?.stop();
     this.keyboard?.destroy();
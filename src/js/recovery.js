@@ -0,0 +1,40 @@
+// Recovery Window — shown when the database fails to open at startup
+
+const Recovery = {
+  async init() {
+    const failure = await Utils.invoke('get_startup_error');
+    if (failure) this.render(failure);
+
+    document.querySelectorAll('[data-recovery]').forEach(btn => {
+      btn.addEventListener('click', () => this.run(btn.dataset.recovery));
+    });
+  },
+
+  render(failure) {
+    document.getElementById('recovery-message').textContent = failure.message;
+    document.getElementById('recovery-detail').textContent =
+      `${failure.db_path}\n${failure.occurred_at}\n${failure.detail}`;
+    document.getElementById('recovery-log-path').textContent = failure.log_path;
+    document.getElementById('btn-restore').disabled = !failure.backup_available;
+  },
+
+  async run(command) {
+    const status = document.getElementById('recovery-status');
+    const buttons = document.querySelectorAll('[data-recovery]');
+    buttons.forEach(b => b.disabled = true);
+    status.textContent = 'Working...';
+
+    try {
+      // On success the backend closes this window
+      await Utils.invoke(command);
+    } catch (e) {
+      status.textContent = `Still failing: ${Utils.errorMessage(e)}`;
+      const failure = await Utils.invoke('get_startup_error');
+      if (failure) this.render(failure);
+      buttons.forEach(b => b.disabled = false);
+      if (failure) document.getElementById('btn-restore').disabled = !failure.backup_available;
+    }
+  },
+};
+
+document.addEventListener('DOMContentLoaded', () => Recovery.init());
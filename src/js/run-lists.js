@@ -0,0 +1,182 @@
+// Run Lists — scripts prompted one after another, edited in a modal
+
+const RunLists = {
+  lists: [],
+  // The list being edited; its id is empty until it's saved
+  current: null,
+
+  init() {
+    this.modal = document.getElementById('run-lists-modal');
+    this.picker = document.getElementById('run-list-picker');
+    this.nameInput = document.getElementById('run-list-name');
+    this.endActionSelect = document.getElementById('run-list-end-action');
+    this.itemsEl = document.getElementById('run-list-items');
+    this.statusEl = document.getElementById('run-list-status');
+    this.hint = this.statusEl.textContent.trim();
+
+    document.getElementById('btn-run-lists').addEventListener('click', () => this.open());
+    document.getElementById('btn-run-list-close').addEventListener('click', () => this.close());
+    document.getElementById('btn-run-list-save').addEventListener('click', () => this.save());
+    document.getElementById('btn-run-list-start').addEventListener('click', () => this.start());
+    document.getElementById('btn-run-list-delete').addEventListener('click', () => this.remove());
+    document.getElementById('btn-run-list-add').addEventListener('click', () => {
+      const scriptId = App.selectedId || App.scripts[0]?.id;
+      if (!scriptId) return;
+      this.current.items.push({ script_id: scriptId, mode: null, speed: null, gap_seconds: 0 });
+      this.renderItems();
+    });
+    this.picker.addEventListener('change', () => this.select(this.picker.value));
+    this.nameInput.addEventListener('input', () => { this.current.name = this.nameInput.value; });
+    this.endActionSelect.addEventListener('change', () => {
+      this.current.end_action = this.endActionSelect.value;
+    });
+
+    Utils.listen('run-list-changed', (event) => this.showStatus(event.payload));
+  },
+
+  async open() {
+    this.lists = await Utils.invoke('list_run_lists') || [];
+    this.select(this.lists[0]?.id || '');
+    this.showStatus(await Utils.invoke('get_run_list_status'));
+    this.modal.style.display = 'flex';
+  },
+
+  close() {
+    this.modal.style.display = 'none';
+  },
+
+  select(id) {
+    const list = this.lists.find(l => l.id === id);
+    this.current = list
+      ? structuredClone(list)
+      : { id: '', name: '', end_action: 'advance', items: [] };
+    this.render();
+  },
+
+  render() {
+    const newOption = new Option('New Run List', '');
+    this.picker.replaceChildren(...this.lists.map(l => new Option(l.name, l.id)), newOption);
+    this.picker.value = this.current.id;
+    this.nameInput.value = this.current.name;
+    this.endActionSelect.value = this.current.end_action;
+    this.renderItems();
+  },
+
+  // Built with the DOM so titles need no escaping
+  renderItems() {
+    const items = this.current.items;
+    this.itemsEl.replaceChildren(...items.map((item, index) => {
+      const row = document.createElement('div');
+      row.className = 'run-list-item';
+
+      const script = document.createElement('select');
+      script.className = 'select';
+      script.append(...App.scripts.map(s => new Option(s.title || 'Untitled Script', s.id)));
+      script.value = item.script_id;
+      script.addEventListener('change', () => { item.script_id = script.value; });
+
+      const mode = document.createElement('select');
+      mode.className = 'select';
+      mode.title = 'Prompter';
+      mode.append(new Option('Default', ''), new Option('Top Bar', 'notch'), new Option('Floating', 'floating'));
+      mode.value = item.mode || '';
+      mode.addEventListener('change', () => { item.mode = mode.value || null; });
+
+      const speed = document.createElement('input');
+      speed.type = 'number';
+      speed.className = 'text-input';
+      speed.placeholder = 'Speed';
+      speed.title = 'Scroll speed, 10–200; blank for the default';
+      speed.min = 10;
+      speed.max = 200;
+      speed.value = item.speed ?? '';
+      speed.addEventListener('change', () => {
+        item.speed = speed.value === '' ? null : Number(speed.value);
+      });
+
+      const gap = document.createElement('input');
+      gap.type = 'number';
+      gap.className = 'text-input';
+      gap.placeholder = 'Gap';
+      gap.title = 'Seconds to wait after this script';
+      gap.min = 0;
+      gap.value = item.gap_seconds || '';
+      gap.addEventListener('change', () => { item.gap_seconds = Number(gap.value) || 0; });
+
+      const actions = document.createElement('div');
+      actions.className = 'run-list-item-actions';
+      const button = (label, title, onClick) => {
+        const b = document.createElement('button');
+        b.type = 'button';
+        b.className = 'btn-icon';
+        b.innerHTML = label;
+        b.title = title;
+        b.addEventListener('click', () => {
+          onClick();
+          this.renderItems();
+        });
+        return b;
+      };
+      const move = (to) => {
+        if (to < 0 || to >= items.length) return;
+        items.splice(to, 0, items.splice(index, 1)[0]);
+      };
+      actions.append(
+        button('&#8593;', 'Move Up', () => move(index - 1)),
+        button('&#8595;', 'Move Down', () => move(index + 1)),
+        button('&times;', 'Remove', () => items.splice(index, 1)),
+      );
+
+      row.append(script, mode, speed, gap, actions);
+      return row;
+    }));
+  },
+
+  // Resolves to the saved list, or null after showing why it wasn't
+  async save() {
+    try {
+      const saved = await Utils.invoke('save_run_list', { runList: this.current });
+      const at = this.lists.findIndex(l => l.id === saved.id);
+      if (at >= 0) this.lists[at] = saved;
+      else this.lists.push(saved);
+      this.current = structuredClone(saved);
+      this.render();
+      return saved;
+    } catch (e) {
+      this.statusEl.textContent = Utils.errorMessage(e);
+      return null;
+    }
+  },
+
+  async start(allowUnresolved = false) {
+    const saved = await this.save();
+    if (!saved) return;
+    try {
+      await Utils.invoke('start_run_list', { id: saved.id, allowUnresolved });
+      this.close();
+    } catch (e) {
+      if (e?.code === 'unresolved_placeholders' && confirm(`${Utils.errorMessage(e)}. Start anyway?`)) {
+        await this.start(true);
+      } else {
+        this.statusEl.textContent = Utils.errorMessage(e);
+      }
+    }
+  },
+
+  async remove() {
+    if (!this.current.id) {
+      this.select('');
+      return;
+    }
+    if (!confirm(`Delete the run list "${this.current.name}"?`)) return;
+    await Utils.invoke('delete_run_list', { id: this.current.id });
+    this.lists = this.lists.filter(l => l.id !== this.current.id);
+    this.select(this.lists[0]?.id || '');
+  },
+
+  showStatus(status) {
+    this.statusEl.textContent = status
+      ? `Running "${status.name}": ${status.index + 1} of ${status.count}, ${status.title}`
+      : this.hint;
+  },
+};
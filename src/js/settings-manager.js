@@ -10,6 +10,315 @@ const SettingsManager = {
     this.applyToUI();
     this.bindEvents();
     this.setupSystemAppearance();
+    this.loadFolderSync();
+    this.loadWebDavSync();
+    this.loadCueOutputs();
+    this.loadVault();
+    this.loadProfiles();
+    this.loadDataLocation();
+
+    // Changes from elsewhere (profiles, imports)
+    Utils.listen('settings-changed', (event) => {
+      const settings = event.payload?.settings;
+      if (!settings) return;
+      this.settings = settings;
+      this.applyToUI();
+      this.loadProfiles();
+    });
+  },
+
+  async loadFolderSync() {
+    const status = await Utils.invoke('get_folder_sync');
+    if (status) this._renderFolderSync(status);
+  },
+
+  _renderFolderSync(status) {
+    const label = document.getElementById('folder-sync-status');
+    if (!status.folder) {
+      label.textContent = 'Off';
+    } else if (status.last_error) {
+      label.textContent = `${status.folder} — ${status.last_error}`;
+    } else {
+      label.textContent = status.folder;
+    }
+    document.getElementById('btn-folder-sync-off').style.display = status.folder ? '' : 'none';
+  },
+
+  async loadWebDavSync() {
+    const status = await Utils.invoke('get_webdav_sync');
+    if (!status) return;
+    document.getElementById('webdav-url').value = status.url || '';
+    document.getElementById('webdav-username').value = status.username || '';
+    document.getElementById('webdav-interval').value = status.interval_minutes || '';
+    this._renderWebDavSync(status);
+  },
+
+  _renderWebDavSync(status) {
+    const label = document.getElementById('webdav-status');
+    if (!status.url) {
+      label.textContent = 'Off';
+    } else if (status.last_error) {
+      label.textContent = status.last_error;
+    } else if (status.last_synced_at) {
+      label.textContent = `Last synced ${Utils.formatDate(status.last_synced_at)}`;
+    } else {
+      label.textContent = 'Not synced yet';
+    }
+  },
+
+  async _setWebDavSync(config) {
+    try {
+      const status = await Utils.invoke('set_webdav_sync', { config });
+      if (status) this._renderWebDavSync(status);
+    } catch (e) {
+      document.getElementById('webdav-status').textContent = Utils.errorMessage(e);
+    }
+  },
+
+  async loadCueOutputs() {
+    const outputs = await Utils.invoke('get_cue_outputs');
+    if (outputs) this._renderCueOutputs(outputs);
+  },
+
+  _renderCueOutputs(outputs) {
+    const list = document.getElementById('cue-outputs');
+    list.replaceChildren();
+    for (const output of outputs) {
+      const row = document.createElement('div');
+      row.className = 'setting-row';
+
+      const info = document.createElement('div');
+      const name = document.createElement('div');
+      name.className = 'setting-label';
+      name.textContent = output.name;
+      const description = document.createElement('div');
+      description.className = 'setting-description';
+      const cues = output.cues.length ? output.cues.join(', ') : 'every cue';
+      description.textContent = `${this._cueOutputTarget(output)} — ${cues}`;
+      info.append(name, description);
+
+      const actions = document.createElement('div');
+      actions.className = 'setting-actions';
+      const toggle = document.createElement('label');
+      toggle.className = 'toggle';
+      const checkbox = document.createElement('input');
+      checkbox.type = 'checkbox';
+      checkbox.checked = output.enabled;
+      checkbox.addEventListener('change', () => {
+        this._saveCueOutput({ ...output, enabled: checkbox.checked });
+      });
+      const track = document.createElement('div');
+      track.className = 'toggle-track';
+      const thumb = document.createElement('div');
+      thumb.className = 'toggle-thumb';
+      toggle.append(checkbox, track, thumb);
+
+      const test = document.createElement('button');
+      test.className = 'btn-secondary';
+      test.textContent = 'Test';
+      test.addEventListener('click', () => this._testCueOutput(output));
+      const remove = document.createElement('button');
+      remove.className = 'btn-secondary';
+      remove.textContent = 'Remove';
+      remove.addEventListener('click', async () => {
+        const outputs = await Utils.invoke('delete_cue_output', { id: output.id });
+        if (outputs) this._renderCueOutputs(outputs);
+      });
+      actions.append(toggle, test, remove);
+
+      row.append(info, actions);
+      list.append(row);
+    }
+  },
+
+  _cueOutputTarget(output) {
+    switch (output.kind) {
+      case 'webhook': return output.url;
+      case 'osc': return `${output.host}:${output.port}${output.address ? ` ${output.address}` : ''}`;
+      default: return [output.program, ...output.args].join(' ');
+    }
+  },
+
+  // The form's output; "To" is a URL, `host:port [/address]`, or a program
+  // and its arguments, quoted where they contain spaces
+  _cueOutputFromForm() {
+    const kind = document.getElementById('cue-output-kind').value;
+    const target = document.getElementById('cue-output-target').value.trim();
+    const cues = document.getElementById('cue-output-cues').value
+      .split(',').map(c => c.trim()).filter(Boolean);
+    const output = {
+      name: document.getElementById('cue-output-name').value.trim(),
+      cues,
+      enabled: true,
+      kind,
+    };
+    if (kind === 'webhook') {
+      output.url = target;
+    } else if (kind === 'osc') {
+      const [hostPort = '', address] = target.split(/\s+/);
+      const colon = hostPort.lastIndexOf(':');
+      output.host = colon > 0 ? hostPort.slice(0, colon) : hostPort;
+      output.port = colon > 0 ? parseInt(hostPort.slice(colon + 1), 10) || 0 : 0;
+      output.address = address || null;
+    } else {
+      const words = (target.match(/"[^"]*"|\S+/g) || []).map(w => w.replace(/^"(.*)"$/, '$1'));
+      output.program = words[0] || '';
+      output.args = words.slice(1);
+    }
+    return output;
+  },
+
+  async _saveCueOutput(output) {
+    const status = document.getElementById('cue-output-status');
+    try {
+      const outputs = await Utils.invoke('save_cue_output', { output });
+      if (outputs) this._renderCueOutputs(outputs);
+      status.textContent = '';
+      return true;
+    } catch (e) {
+      status.textContent = Utils.errorMessage(e);
+      return false;
+    }
+  },
+
+  async _testCueOutput(output) {
+    const status = document.getElementById('cue-output-status');
+    status.textContent = `Testing ${output.name || 'output'}...`;
+    try {
+      await Utils.invoke('test_cue_output', { output });
+      status.textContent = `${output.name || 'Output'} works`;
+    } catch (e) {
+      status.textContent = Utils.errorMessage(e);
+    }
+  },
+
+  async loadVault() {
+    const status = await Utils.invoke('get_vault_status');
+    if (status) this._renderVault(status);
+  },
+
+  _renderVault(status) {
+    const label = document.getElementById('vault-status');
+    if (!status.enabled) {
+      label.textContent = 'Off';
+    } else if (status.locked) {
+      label.textContent = 'On — locked';
+    } else {
+      label.textContent = 'On';
+    }
+    document.getElementById('btn-vault-toggle').textContent = status.enabled ? 'Turn Off' : 'Turn On';
+    document.getElementById('btn-vault-lock').style.display = status.enabled ? '' : 'none';
+    document.querySelectorAll('.vault-only').forEach(row => {
+      row.style.display = status.enabled ? '' : 'none';
+    });
+    document.getElementById('vault-auto-lock').value = status.auto_lock_minutes || '';
+    this.vaultEnabled = status.enabled;
+  },
+
+  async _runVault(command, args) {
+    try {
+      const status = await Utils.invoke(command, args);
+      if (status) this._renderVault(status);
+      document.getElementById('vault-passphrase').value = '';
+      document.getElementById('vault-new-passphrase').value = '';
+      return true;
+    } catch (e) {
+      document.getElementById('vault-status').textContent = Utils.errorMessage(e);
+      return false;
+    }
+  },
+
+  async loadDataLocation() {
+    const location = await Utils.invoke('get_data_location');
+    if (!location) return;
+    const notes = {
+      flag: ' (set with --data-dir)',
+      environment: ' (set with WHISPR_DATA_DIR)',
+      portable: ' (portable mode)',
+    };
+    document.getElementById('data-location').textContent = location.dir + (notes[location.source] || '');
+  },
+
+  async loadProfiles() {
+    const profiles = await Utils.invoke('list_profiles');
+    if (profiles) this._renderProfiles(profiles);
+  },
+
+  _renderProfiles(profiles) {
+    const select = document.getElementById('profile-select');
+    select.innerHTML = '<option value="">None</option>';
+    profiles.forEach(p => {
+      const option = document.createElement('option');
+      option.value = p.id;
+      option.textContent = p.name;
+      select.appendChild(option);
+    });
+    select.value = profiles.find(p => p.active)?.id || '';
+
+    const exportSource = document.getElementById('export-source');
+    exportSource.innerHTML = '<option value="">Current Settings</option>';
+    profiles.forEach(p => {
+      const option = document.createElement('option');
+      option.value = p.id;
+      option.textContent = `Profile: ${p.name}`;
+      exportSource.appendChild(option);
+    });
+
+    const selected = !!select.value;
+    ['btn-profile-duplicate', 'btn-profile-rename', 'btn-profile-delete'].forEach(id => {
+      document.getElementById(id).disabled = !selected;
+    });
+  },
+
+  async _runProfile(command, args) {
+    try {
+      const profiles = await Utils.invoke(command, args);
+      if (profiles) this._renderProfiles(profiles);
+    } catch (e) {
+      document.getElementById('profile-status').textContent = Utils.errorMessage(e);
+    }
+  },
+
+  _importGroups() {
+    return [...document.querySelectorAll('[data-import-group]')]
+      .filter(box => box.checked)
+      .map(box => box.dataset.importGroup);
+  },
+
+  async _previewImport() {
+    const list = document.getElementById('import-changes');
+    const status = document.getElementById('import-status');
+    try {
+      const preview = await Utils.invoke('preview_settings_import', {
+        path: this.importPath,
+        groups: this._importGroups(),
+      });
+      if (!preview) return;
+      list.innerHTML = '';
+      preview.changes.forEach(change => {
+        const item = document.createElement('li');
+        item.textContent = `${change.label}: ${change.current} → ${change.incoming}`;
+        list.appendChild(item);
+      });
+      if (preview.changes.length === 0) {
+        list.innerHTML = '<li>No changes</li>';
+      }
+      status.textContent = preview.name ? `From "${preview.name}"` : 'Review the changes before applying them';
+      document.getElementById('btn-import-apply').disabled = preview.changes.length === 0;
+      document.getElementById('import-preview').style.display = '';
+    } catch (e) {
+      status.textContent = Utils.errorMessage(e);
+      document.getElementById('import-preview').style.display = 'none';
+    }
+  },
+
+  async _setFolderSync(folder) {
+    try {
+      const status = await Utils.invoke('set_folder_sync', { folder });
+      if (status) this._renderFolderSync(status);
+    } catch (e) {
+      document.getElementById('folder-sync-status').textContent = Utils.errorMessage(e);
+    }
   },
 
   applyToUI() {
@@ -23,6 +332,8 @@ const SettingsManager = {
     document.getElementById('setting-prompter-mode').value = s.prompter_mode;
     document.getElementById('setting-countdown').value = s.countdown_seconds;
     document.getElementById('setting-end-action').value = s.end_action;
+    document.getElementById('setting-hold-to-scroll').checked = s.hold_to_scroll;
+    document.getElementById('setting-hold-shortcut').value = s.hold_shortcut;
 
     // Top bar tab
     this._setSlider('setting-notch-font-size', s.notch_font_size, 'val-notch-font-size', 'pt');
@@ -132,12 +443,188 @@ const SettingsManager = {
     // Toggles
     this._bindToggle('setting-notch-show-timer', 'notch_show_timer');
     this._bindToggle('setting-mirror-mode', 'mirror_mode');
+    document.getElementById('setting-hold-to-scroll').addEventListener('change', async (e) => {
+      const status = document.getElementById('hold-key-status');
+      try {
+        this.settings = await Utils.invoke('update_setting', { key: 'hold_to_scroll', value: e.target.checked });
+      } catch (err) {
+        // Usually the hold key is taken by another program
+        status.textContent = Utils.errorMessage(err);
+        this.applyToUI();
+      }
+    });
+    this._bindShortcut('setting-hold-shortcut', 'hold_shortcut', 'hold-key-status');
 
     // Text color picker
     document.getElementById('setting-text-color').addEventListener('change', (e) => {
       this._update('text_color_hex', e.target.value);
     });
 
+    // Folder sync
+    document.getElementById('btn-folder-sync-choose').addEventListener('click', async () => {
+      if (!window.__TAURI__) return;
+      const folder = await window.__TAURI__.dialog.open({ directory: true, multiple: false });
+      if (folder) this._setFolderSync(folder);
+    });
+    document.getElementById('btn-folder-sync-off').addEventListener('click', () => {
+      this._setFolderSync(null);
+    });
+
+    // Profiles
+    document.getElementById('profile-select').addEventListener('change', (e) => {
+      // Profiles are left by activating another one, not by choosing None
+      if (e.target.value) this._runProfile('activate_profile', { id: e.target.value });
+      else this.loadProfiles();
+    });
+    document.getElementById('btn-profile-new').addEventListener('click', () => {
+      const name = prompt('Profile name (saves the current settings):');
+      if (name) this._runProfile('create_profile', { name });
+    });
+    document.getElementById('btn-profile-duplicate').addEventListener('click', () => {
+      const id = document.getElementById('profile-select').value;
+      if (id) this._runProfile('duplicate_profile', { id });
+    });
+    document.getElementById('btn-profile-rename').addEventListener('click', () => {
+      const select = document.getElementById('profile-select');
+      if (!select.value) return;
+      const name = prompt('Rename profile:', select.selectedOptions[0].textContent);
+      if (name) this._runProfile('rename_profile', { id: select.value, name });
+    });
+    document.getElementById('btn-profile-delete').addEventListener('click', () => {
+      const select = document.getElementById('profile-select');
+      if (!select.value) return;
+      if (confirm(`Delete the profile "${select.selectedOptions[0].textContent}"?`)) {
+        this._runProfile('delete_profile', { id: select.value });
+      }
+    });
+
+    // Share settings
+    document.getElementById('btn-settings-export').addEventListener('click', async () => {
+      if (!window.__TAURI__) return;
+      const path = await window.__TAURI__.dialog.save({
+        defaultPath: 'whispr-settings.json',
+        filters: [{ name: 'Whispr Settings', extensions: ['json'] }],
+      });
+      if (!path) return;
+      const profileId = document.getElementById('export-source').value || null;
+      const status = document.getElementById('import-status');
+      try {
+        await Utils.invoke('export_settings', { path, profileId });
+        status.textContent = `Exported to ${path}`;
+      } catch (e) {
+        status.textContent = Utils.errorMessage(e);
+      }
+    });
+    document.getElementById('btn-settings-import').addEventListener('click', async () => {
+      if (!window.__TAURI__) return;
+      const path = await window.__TAURI__.dialog.open({
+        multiple: false,
+        filters: [{ name: 'Whispr Settings', extensions: ['json'] }],
+      });
+      if (!path) return;
+      this.importPath = path;
+      this._previewImport();
+    });
+    document.querySelectorAll('[data-import-group]').forEach(box => {
+      box.addEventListener('change', () => this._previewImport());
+    });
+    document.getElementById('btn-import-cancel').addEventListener('click', () => {
+      document.getElementById('import-preview').style.display = 'none';
+      this.importPath = null;
+    });
+    document.getElementById('btn-import-apply').addEventListener('click', async () => {
+      const status = document.getElementById('import-status');
+      try {
+        const updated = await Utils.invoke('import_settings', {
+          path: this.importPath,
+          groups: this._importGroups(),
+        });
+        if (updated) {
+          this.settings = updated;
+          this.applyToUI();
+        }
+        status.textContent = 'Imported';
+        document.getElementById('import-preview').style.display = 'none';
+      } catch (e) {
+        status.textContent = Utils.errorMessage(e);
+      }
+    });
+
+    // Encryption
+    document.getElementById('btn-vault-toggle').addEventListener('click', () => {
+      const passphrase = document.getElementById('vault-passphrase').value;
+      if (!passphrase) return;
+      this._runVault(this.vaultEnabled ? 'disable_encryption' : 'enable_encryption', { passphrase });
+    });
+    document.getElementById('btn-vault-change').addEventListener('click', async () => {
+      const current = document.getElementById('vault-passphrase').value;
+      const newPassphrase = document.getElementById('vault-new-passphrase').value;
+      if (!current || !newPassphrase) return;
+      if (await this._runVault('change_passphrase', { current, newPassphrase })) {
+        document.getElementById('vault-status').textContent = 'Passphrase changed';
+      }
+    });
+    document.getElementById('vault-auto-lock').addEventListener('change', (e) => {
+      const minutes = e.target.value ? parseInt(e.target.value, 10) : null;
+      this._runVault('set_auto_lock', { minutes });
+    });
+    document.getElementById('btn-vault-lock').addEventListener('click', async () => {
+      await this._runVault('lock_library');
+      this.loadVault();
+    });
+
+    // WebDAV sync
+    document.getElementById('btn-webdav-save').addEventListener('click', () => {
+      const url = document.getElementById('webdav-url').value.trim();
+      if (!url) return;
+      const interval = document.getElementById('webdav-interval').value;
+      this._setWebDavSync({
+        url,
+        username: document.getElementById('webdav-username').value.trim(),
+        password: document.getElementById('webdav-password').value,
+        interval_minutes: interval ? parseInt(interval, 10) : null,
+      });
+      document.getElementById('webdav-password').value = '';
+    });
+    document.getElementById('btn-webdav-off').addEventListener('click', () => {
+      document.getElementById('webdav-url').value = '';
+      this._setWebDavSync(null);
+    });
+    document.getElementById('btn-webdav-sync').addEventListener('click', async () => {
+      const label = document.getElementById('webdav-status');
+      label.textContent = 'Syncing...';
+      try {
+        await Utils.invoke('sync_webdav_now');
+      } catch (e) {
+        // Reported through the status below
+      }
+      const status = await Utils.invoke('get_webdav_sync');
+      if (status) this._renderWebDavSync(status);
+    });
+
+    // Cue outputs
+    const cueKind = document.getElementById('cue-output-kind');
+    const cueTarget = document.getElementById('cue-output-target');
+    const targetHints = {
+      webhook: 'http://localhost:8080/cue',
+      osc: '127.0.0.1:8000',
+      command: 'program {cue} {argument}',
+    };
+    cueTarget.placeholder = targetHints[cueKind.value];
+    cueKind.addEventListener('change', () => {
+      cueTarget.placeholder = targetHints[cueKind.value];
+    });
+    document.getElementById('btn-cue-output-test').addEventListener('click', () => {
+      this._testCueOutput(this._cueOutputFromForm());
+    });
+    document.getElementById('btn-cue-output-add').addEventListener('click', async () => {
+      if (await this._saveCueOutput(this._cueOutputFromForm())) {
+        document.getElementById('cue-output-name').value = '';
+        cueTarget.value = '';
+        document.getElementById('cue-output-cues').value = '';
+      }
+    });
+
     // Glow cards
     document.querySelectorAll('[data-glow]').forEach(card => {
       card.addEventListener('click', () => {
@@ -181,11 +668,41 @@ const SettingsManager = {
     });
   },
 
+  // Records the next key combination pressed while the input has focus,
+  // in the form the global shortcut plugin parses (e.g. "Ctrl+Shift+Space").
+  _bindShortcut(id, key, statusId) {
+    const input = document.getElementById(id);
+    const status = document.getElementById(statusId);
+    const hint = status.textContent;
+    input.addEventListener('keydown', async (e) => {
+      e.preventDefault();
+      if (['Control', 'Shift', 'Alt', 'Meta'].includes(e.key)) return;
+      const parts = [];
+      if (e.ctrlKey) parts.push('Ctrl');
+      if (e.altKey) parts.push('Alt');
+      if (e.shiftKey) parts.push('Shift');
+      if (e.metaKey) parts.push('Super');
+      parts.push(e.code);
+      const value = parts.join('+');
+      try {
+        this.settings = await Utils.invoke('update_setting', { key, value });
+        status.textContent = hint;
+      } catch (err) {
+        status.textContent = Utils.errorMessage(err);
+      }
+      this.applyToUI();
+      input.blur();
+    });
+  },
+
   async _update(key, value) {
-    const updated = await Utils.invoke('update_setting', { key, value: value.toString() });
-    if (updated) {
-      this.settings = updated;
-      Utils.emit('settings-changed', updated);
+    try {
+      const updated = await Utils.invoke('update_setting', { key, value });
+      if (updated) this.settings = updated;
+    } catch (e) {
+      // Rejected values (out of range, unknown option) — show the stored value again
+      console.warn('Setting rejected:', Utils.errorMessage(e));
+      this.applyToUI();
     }
   },
 };
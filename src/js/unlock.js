@@ -0,0 +1,31 @@
+// Unlock Window — asks for the passphrase while the library is encrypted and locked
+
+const Unlock = {
+  init() {
+    document.getElementById('unlock-form').addEventListener('submit', (e) => {
+      e.preventDefault();
+      this.submit();
+    });
+  },
+
+  async submit() {
+    const input = document.getElementById('unlock-passphrase');
+    const button = document.getElementById('btn-unlock');
+    const status = document.getElementById('unlock-status');
+    if (!input.value) return;
+
+    button.disabled = true;
+    status.textContent = 'Unlocking...';
+    try {
+      // On success the backend closes this window
+      await Utils.invoke('unlock_library', { passphrase: input.value });
+    } catch (e) {
+      status.textContent = Utils.errorMessage(e);
+      input.value = '';
+      input.focus();
+      button.disabled = false;
+    }
+  },
+};
+
+document.addEventListener('DOMContentLoaded', () => Unlock.init());
@@ -3,7 +3,30 @@
 const Utils = {
   wordCount(text) {
     if (!text || !text.trim()) return 0;
-    return text.trim().split(/\s+/).length;
+    return text.trim().split(/\s+/).reduce((n, token) => n + this.wordPieces(token).length, 0);
+  },
+
+  /**
+   * Split one whitespace-free token into the words the prompter paces by,
+   * matching text::word_count in the backend: each Chinese or Japanese
+   * character is a word, punctuation right after one stays with it.
+   */
+  wordPieces(token) {
+    const cjk = /[\u3040-\u30ff\u31f0-\u31ff\u3400-\u4dbf\u4e00-\u9fff\uf900-\ufaff\uff66-\uff9f\u{20000}-\u{2fa1f}]/u;
+    const pieces = [];
+    let inRun = false;
+    for (const ch of token) {
+      if (cjk.test(ch)) {
+        pieces.push(ch);
+        inRun = false;
+      } else if (inRun || (pieces.length && !/[\p{L}\p{N}]/u.test(ch))) {
+        pieces[pieces.length - 1] += ch;
+      } else {
+        pieces.push(ch);
+        inRun = true;
+      }
+    }
+    return pieces;
   },
 
   charCount(text) {
@@ -23,6 +46,13 @@ const Utils = {
     return `${m}:${s.toString().padStart(2, '0')}`;
   },
 
+  // "2:30", "1:02:30" or plain seconds; null when it isn't a duration
+  parseDuration(text) {
+    const parts = (text || '').trim().split(':');
+    if (parts.length > 3 || parts.some(p => !/^\d+(\.\d+)?$/.test(p))) return null;
+    return parts.reduce((total, p) => total * 60 + parseFloat(p), 0);
+  },
+
   formatDate(isoString) {
     const d = new Date(isoString);
     const now = new Date();
@@ -79,6 +109,11 @@ const Utils = {
     return null;
   },
 
+  // Commands reject with { code, message, details }
+  errorMessage(e) {
+    return e?.message ?? `${e}`;
+  },
+
   async listen(event, handler) {
     if (window.__TAURI__) {
       return window.__TAURI__.event.listen(event, handler);
@@ -104,6 +139,26 @@ const Utils = {
     return div.innerHTML;
   },
 
+  /**
+   * One prompter word with its emphasis, preceded by any section title or
+   * cues that come before it
+   */
+  wordHtml(word, cls, attrs = '') {
+    let html = '';
+    if (word.section) {
+      html += `<span class="section-marker">${this.escapeHtml(word.section)}</span>`;
+    }
+    for (const cue of word.cues || []) {
+      const label = cue.argument ? `${cue.name} ${cue.argument}` : cue.name;
+      html += `<span class="cue-marker">${this.escapeHtml(label)}</span>`;
+    }
+    if (word.strong) cls += ' strong';
+    if (word.em) cls += ' em';
+    if (word.placeholder) cls += ' placeholder';
+    const space = word.joined ? '' : ' ';
+    return `${html}<span class="${cls}"${attrs}>${this.escapeHtml(word.text)}${space}</span>`;
+  },
+
   stripMarkdown(text) {
     if (!text) return '';
     return text